@@ -0,0 +1,107 @@
+//! Compare binding state for a frame of draw items with raw GL calls and with the `*_cached`
+//! methods of `feature = "state-cache"`.
+//!
+//! The items are sorted by program, then texture, like a renderer would, so most binds are
+//! redundant. Run with `cargo bench --features state-cache --bench state_cache`. Needs a display.
+
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+use ezgl::{
+    gl::{self, HasContext, NativeProgram, NativeTexture, NativeVertexArray},
+    Ezgl,
+};
+use std::time::{Duration, Instant};
+
+const ITEMS: usize = 10_000;
+const FRAMES: u32 = 100;
+
+const VERTEX: &str = "void main() { gl_Position = vec4(0.0); }";
+const FRAGMENT: &str = "out vec4 color; void main() { color = vec4(1.0); }";
+
+#[derive(Clone, Copy)]
+struct Item {
+    program: NativeProgram,
+    vertex_array: NativeVertexArray,
+    texture: NativeTexture,
+}
+
+fn main() {
+    common::with_ezgl(Ezgl::builder(), |ezgl| {
+        let header = ezgl.glsl_version_header();
+        let programs = (0..4)
+            .map(|_| {
+                ezgl.program_from_sources(
+                    &format!("{}{}", header, VERTEX),
+                    &format!("{}{}", header, FRAGMENT),
+                )
+                .unwrap()
+            })
+            .collect::<Vec<_>>();
+        let vertex_arrays = (0..8)
+            .map(|_| unsafe { ezgl.create_vertex_array().unwrap() })
+            .collect::<Vec<_>>();
+        let textures = (0..16)
+            .map(|_| unsafe { ezgl.create_texture().unwrap() })
+            .collect::<Vec<_>>();
+
+        let items = (0..ITEMS)
+            .map(|i| Item {
+                program: programs[i * programs.len() / ITEMS],
+                vertex_array: vertex_arrays[i * vertex_arrays.len() / ITEMS],
+                texture: textures[i * textures.len() / ITEMS],
+            })
+            .collect::<Vec<_>>();
+
+        // alternate so driver warmup doesn't favor either
+        let mut raw = Duration::ZERO;
+        let mut cached = Duration::ZERO;
+        let mut cached_calls = 0;
+        for _ in 0..FRAMES {
+            unsafe { ezgl.active_texture(gl::TEXTURE0) };
+            let start = Instant::now();
+            for item in &items {
+                unsafe {
+                    ezgl.use_program(Some(item.program));
+                    ezgl.bind_vertex_array(Some(item.vertex_array));
+                    ezgl.bind_texture(gl::TEXTURE_2D, Some(item.texture));
+                }
+            }
+            unsafe { ezgl.finish() };
+            raw += start.elapsed();
+
+            ezgl.invalidate_state_cache();
+            ezgl.active_texture_cached(gl::TEXTURE0);
+            let calls = ezgl.state_cache_calls();
+            let start = Instant::now();
+            for item in &items {
+                ezgl.use_program_cached(Some(item.program));
+                ezgl.bind_vertex_array_cached(Some(item.vertex_array));
+                ezgl.bind_texture_cached(gl::TEXTURE_2D, Some(item.texture));
+            }
+            unsafe { ezgl.finish() };
+            cached += start.elapsed();
+            cached_calls += ezgl.state_cache_calls() - calls;
+        }
+
+        println!("raw:    {} binds, {:?} per frame", ITEMS * 3, raw / FRAMES);
+        println!(
+            "cached: {} binds, {:?} per frame",
+            cached_calls / FRAMES as u64,
+            cached / FRAMES
+        );
+
+        unsafe {
+            ezgl.reset_state();
+            for program in programs {
+                ezgl.delete_program(program);
+            }
+            for vertex_array in vertex_arrays {
+                ezgl.delete_vertex_array(vertex_array);
+            }
+            for texture in textures {
+                ezgl.delete_texture(texture);
+            }
+        }
+    });
+}
@@ -0,0 +1,42 @@
+use ezgl::{app::EzglApp, gl, Ezgl};
+use gl::HasContext;
+
+// the same as the simple example, without the winit boilerplate
+#[derive(Default)]
+struct App {
+    width: u32,
+    height: u32,
+}
+
+impl EzglApp for App {
+    fn init(&mut self, ezgl: &Ezgl) {
+        unsafe { ezgl.clear_color(0.1, 0.2, 0.3, 1.0) };
+    }
+
+    fn render(&mut self, ezgl: &Ezgl) {
+        unsafe { ezgl.clear(gl::COLOR_BUFFER_BIT) };
+    }
+
+    fn resize(&mut self, _: &Ezgl, width: u32, height: u32) {
+        self.width = width;
+        self.height = height;
+    }
+
+    fn window_event(&mut self, ezgl: &Ezgl, event: &winit::event::WindowEvent) {
+        if let winit::event::WindowEvent::CursorMoved { position, .. } = event {
+            unsafe {
+                ezgl.clear_color(
+                    position.x as f32 / self.width as f32,
+                    position.y as f32 / self.height as f32,
+                    0.3,
+                    1.0,
+                )
+            };
+        }
+    }
+}
+
+fn main() {
+    env_logger::init();
+    ezgl::app::run(App::default()).unwrap();
+}
@@ -0,0 +1,126 @@
+use ezgl::{
+    app::EzglApp,
+    gl::{self, Context, HasContext},
+    winit::{dpi::PhysicalSize, event::Event, event::WindowEvent},
+    Ezgl,
+};
+use imgui_glow_renderer::{Renderer, SimpleTextureMap};
+use imgui_winit_support::{HiDpiMode, WinitPlatform};
+use std::{sync::Arc, time::Instant};
+
+// imgui-glow-renderer uses the same version of glow as ezgl, so it can draw with the context
+// ezgl already loaded instead of loading its own
+struct App {
+    imgui: imgui::Context,
+    platform: Option<WinitPlatform>,
+    renderer: Option<Renderer>,
+    textures: SimpleTextureMap,
+    glow: Option<Arc<Context>>,
+    last_frame: Instant,
+    clear_color: [f32; 3],
+}
+
+impl App {
+    fn new() -> Self {
+        let mut imgui = imgui::Context::create();
+        imgui.set_ini_filename(None);
+
+        App {
+            imgui,
+            platform: None,
+            renderer: None,
+            textures: SimpleTextureMap::default(),
+            glow: None,
+            last_frame: Instant::now(),
+            clear_color: [0.1, 0.2, 0.3],
+        }
+    }
+
+    fn handle_event(&mut self, ezgl: &Ezgl, event: WindowEvent) {
+        let window = ezgl.window().unwrap();
+        let event: Event<()> = Event::WindowEvent {
+            window_id: window.id(),
+            event,
+        };
+        self.platform
+            .as_mut()
+            .unwrap()
+            .handle_event(self.imgui.io_mut(), window, &event);
+    }
+}
+
+impl EzglApp for App {
+    fn init(&mut self, ezgl: &Ezgl) {
+        let mut platform = WinitPlatform::new(&mut self.imgui);
+        platform.attach_window(
+            self.imgui.io_mut(),
+            ezgl.window().unwrap(),
+            HiDpiMode::Default,
+        );
+
+        // if GL doesn't encode sRGB when writing to the window, imgui's shader has to
+        let output_srgb = !ezgl.framebuffer_srgb_state().encodes();
+
+        let glow = ezgl.glow_context();
+        let renderer =
+            Renderer::initialize(&glow, &mut self.imgui, &mut self.textures, output_srgb)
+                .expect("Could not create imgui renderer");
+
+        self.platform = Some(platform);
+        self.renderer = Some(renderer);
+        self.glow = Some(glow);
+    }
+
+    fn render(&mut self, ezgl: &Ezgl) {
+        let window = ezgl.window().unwrap();
+        let platform = self.platform.as_mut().unwrap();
+
+        let now = Instant::now();
+        self.imgui.io_mut().update_delta_time(now - self.last_frame);
+        self.last_frame = now;
+
+        platform
+            .prepare_frame(self.imgui.io_mut(), window)
+            .expect("Could not prepare imgui frame");
+        let ui = self.imgui.frame();
+        ui.window("ezgl").build(|| {
+            ui.text(format!("{} frames", ezgl.frame_count()));
+            ui.color_edit3("clear color", &mut self.clear_color);
+        });
+        platform.prepare_render(ui, window);
+        let draw_data = self.imgui.render();
+
+        let [r, g, b] = self.clear_color;
+        unsafe {
+            ezgl.clear_color(r, g, b, 1.0);
+            ezgl.clear(gl::COLOR_BUFFER_BIT);
+        }
+
+        // the renderer restores the state it finds, so start it from a known baseline rather than
+        // whatever the scene left bound
+        ezgl.reset_state();
+        self.renderer
+            .as_mut()
+            .unwrap()
+            .render(self.glow.as_ref().unwrap(), &self.textures, draw_data)
+            .expect("Could not render imgui");
+
+        // imgui draws through glow directly, so ezgl's state cache didn't see any of it
+        ezgl.reset_state();
+    }
+
+    fn resize(&mut self, ezgl: &Ezgl, width: u32, height: u32) {
+        // the runner already resized the surface and viewport, but imgui needs to know too
+        self.handle_event(ezgl, WindowEvent::Resized(PhysicalSize::new(width, height)));
+    }
+
+    fn window_event(&mut self, ezgl: &Ezgl, event: &WindowEvent) {
+        self.handle_event(ezgl, event.clone());
+    }
+}
+
+fn main() {
+    env_logger::init();
+    // the renderer's objects are deleted along with the context when the window closes
+    ezgl::app::run(App::new()).unwrap();
+}
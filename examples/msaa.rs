@@ -1,5 +1,8 @@
 fn main() {
-    use ezgl::{gl, Ezgl};
+    use ezgl::{
+        framebuffer::{BlitFilter, Rect},
+        gl, Ezgl,
+    };
     use gl::{HasContext, NativeFramebuffer};
     use winit::{
         application::ApplicationHandler,
@@ -111,9 +114,8 @@ fn main() {
 
                 ezgl.use_program(Some(program));
 
-                // now requires a vertex array be bound?
-                let triangle_vertex_array = ezgl.create_vertex_array().unwrap();
-                ezgl.bind_vertex_array(Some(triangle_vertex_array));
+                // core profile has no default vertex array
+                ezgl.ensure_default_vao().unwrap();
 
                 fb
             };
@@ -139,20 +141,16 @@ fn main() {
                     ezgl.draw_arrays(gl::TRIANGLES, 0, 3);
 
                     // 3. copy multisampled buffer to backbuffer
-                    ezgl.bind_framebuffer(gl::READ_FRAMEBUFFER, self.fb);
-                    ezgl.bind_framebuffer(gl::DRAW_FRAMEBUFFER, None);
-                    ezgl.blit_framebuffer(
-                        0,
-                        0,
-                        size.width as i32,
-                        size.height as i32,
-                        0,
-                        0,
-                        size.width as i32,
-                        size.height as i32,
+                    let rect = Rect::new(0, 0, size.width as i32, size.height as i32);
+                    ezgl.blit(
+                        self.fb,
+                        None,
+                        rect,
+                        rect,
                         gl::COLOR_BUFFER_BIT,
-                        gl::NEAREST,
-                    );
+                        BlitFilter::Nearest,
+                    )
+                    .unwrap();
 
                     ezgl.swap_buffers().unwrap();
                 },
@@ -0,0 +1,149 @@
+//! A scaffold for simple winit apps, enabled with `feature = "winit"`.
+//!
+//! Implement [EzglApp] and pass it to [run], which creates the event loop, window, and [Ezgl],
+//! and calls back into the app as events arrive.
+//!
+//! ```no_run
+//! use ezgl::{app::EzglApp, gl::{self, HasContext}, Ezgl};
+//!
+//! struct App;
+//!
+//! impl EzglApp for App {
+//!     fn init(&mut self, ezgl: &Ezgl) {
+//!         unsafe { ezgl.clear_color(0.1, 0.2, 0.3, 1.0) };
+//!     }
+//!
+//!     fn render(&mut self, ezgl: &Ezgl) {
+//!         unsafe { ezgl.clear(gl::COLOR_BUFFER_BIT) };
+//!     }
+//!
+//!     fn resize(&mut self, _: &Ezgl, _: u32, _: u32) {}
+//! }
+//!
+//! ezgl::app::run(App).unwrap();
+//! ```
+
+use crate::{gl::HasContext, Error, Ezgl, Result};
+use winit::{
+    application::ApplicationHandler,
+    event::WindowEvent,
+    event_loop::{ActiveEventLoop, EventLoop},
+    window::{WindowAttributes, WindowId},
+};
+
+/// An app driven by [run].
+pub trait EzglApp {
+    /// Called once after the window and context are set up.
+    fn init(&mut self, ezgl: &Ezgl);
+
+    /// Draw a frame. Buffers are swapped afterward, and another frame is requested right away.
+    fn render(&mut self, ezgl: &Ezgl);
+
+    /// Called after the window was resized. The surface and the viewport have already been
+    /// resized to `width` by `height`.
+    fn resize(&mut self, ezgl: &Ezgl, width: u32, height: u32);
+
+    /// Handle any other window event, e.g. input. Does nothing by default.
+    fn window_event(&mut self, ezgl: &Ezgl, event: &WindowEvent) {
+        let _ = (ezgl, event);
+    }
+
+    /// Get the attributes to create the window with. Defaults to winit's defaults.
+    fn window_attributes(&self) -> WindowAttributes {
+        WindowAttributes::default()
+    }
+}
+
+/// Run an app until its window is closed.
+///
+/// The window is created with [EzglApp::window_attributes] and set up with
+/// [Ezgl::with_winit_window_owned]. If swapping buffers returns [Error::SurfaceLost], the surface
+/// is recreated and rendering continues. When the window is closed, the [Ezgl] is torn down with
+/// [Ezgl::destroy]. Returns any other error from setup, swapping buffers, or teardown.
+///
+/// # Panics
+///
+/// Panics if the event loop or the window can't be created.
+pub fn run<A: EzglApp>(app: A) -> Result<()> {
+    let event_loop = EventLoop::new().expect("Could not create event loop");
+    let mut runner = Runner {
+        app,
+        ezgl: None,
+        result: Ok(()),
+    };
+    event_loop.run_app(&mut runner).expect("Event loop failed");
+    runner.result
+}
+
+struct Runner<A> {
+    app: A,
+    ezgl: Option<Ezgl>,
+    result: Result<()>,
+}
+
+impl<A> Runner<A> {
+    fn fail(&mut self, event_loop: &ActiveEventLoop, err: Error) {
+        self.result = Err(err);
+        event_loop.exit();
+    }
+}
+
+impl<A: EzglApp> ApplicationHandler for Runner<A> {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        if self.ezgl.is_some() {
+            return;
+        }
+
+        let window = event_loop
+            .create_window(self.app.window_attributes())
+            .expect("Could not create window");
+        let ezgl = match Ezgl::with_winit_window_owned(window, None) {
+            Ok(ezgl) => ezgl,
+            Err(err) => return self.fail(event_loop, err),
+        };
+
+        self.app.init(&ezgl);
+        ezgl.window().unwrap().request_redraw();
+        self.ezgl = Some(ezgl);
+    }
+
+    fn window_event(&mut self, event_loop: &ActiveEventLoop, _: WindowId, event: WindowEvent) {
+        let Some(ezgl) = self.ezgl.as_mut() else {
+            return;
+        };
+
+        match event {
+            WindowEvent::RedrawRequested => {
+                self.app.render(ezgl);
+
+                let result = match ezgl.swap_buffers() {
+                    Err(Error::SurfaceLost(_)) => {
+                        let size = ezgl.window().unwrap().inner_size();
+                        ezgl.recreate_surface(size.width, size.height)
+                    }
+                    result => result,
+                };
+                if let Err(err) = result {
+                    return self.fail(event_loop, err);
+                }
+
+                ezgl.window().unwrap().request_redraw();
+            }
+
+            WindowEvent::Resized(size) => {
+                ezgl.resize_winit(size);
+                unsafe { ezgl.viewport(0, 0, size.width as i32, size.height as i32) };
+                self.app.resize(ezgl, size.width, size.height);
+            }
+
+            WindowEvent::CloseRequested => {
+                if let Err(err) = self.ezgl.take().unwrap().destroy() {
+                    return self.fail(event_loop, err);
+                }
+                event_loop.exit();
+            }
+
+            event => self.app.window_event(ezgl, &event),
+        }
+    }
+}
@@ -0,0 +1,652 @@
+//! Configurable ezgl setup.
+
+use crate::{
+    config_template, create_display, default_debug_callback,
+    gl::{Context, DebugCallback, HasContext},
+    install_debug_callback, surface_attributes, Error, Ezgl, EzglHandle, Reg, Result,
+};
+use glutin::{
+    config::{Config, ConfigSurfaceTypes, ConfigTemplate, ConfigTemplateBuilder, GlConfig},
+    context::{
+        ContextApi, ContextAttributesBuilder, NotCurrentGlContext, PossiblyCurrentContext,
+        ReleaseBehavior, Version,
+    },
+    display::{Display, GlDisplay},
+    surface::{Surface, SurfaceAttributes, WindowSurface},
+};
+use raw_window_handle::{
+    HasRawDisplayHandle, HasRawWindowHandle, RawDisplayHandle, RawWindowHandle,
+};
+use std::{
+    num::NonZeroU32,
+    rc::Rc,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// How long to wait between attempts when [EzglBuilder::create_retries] is set.
+const RETRY_DELAY: Duration = Duration::from_millis(100);
+
+/// Desktop GL versions tried in order when the one requested with [EzglBuilder::gl_version] isn't
+/// available.
+const VERSION_LADDER: [(u8, u8); 5] = [(4, 6), (4, 5), (4, 3), (4, 1), (3, 3)];
+
+/// A filter set with [EzglBuilder::config_filter].
+type ConfigFilter = Box<dyn Fn(&Config) -> bool>;
+
+/// Scheduling priority of the context relative to other contexts on the same GPU.
+///
+/// See [Ezgl::context_priority].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContextPriority {
+    /// Lower priority than most contexts.
+    Low,
+    /// The default priority.
+    Medium,
+    /// Higher priority than most contexts, e.g. for a compositor or a VR runtime.
+    High,
+}
+
+/// Whether the back buffer is kept across swaps, for [EzglBuilder::swap_behavior].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SwapBehavior {
+    /// The contents of the back buffer are undefined after a swap, so every frame must be redrawn
+    /// in full.
+    #[default]
+    Destroyed,
+    /// The contents of the back buffer are kept after a swap, so only the parts that changed need
+    /// to be redrawn.
+    Preserved,
+}
+
+/// The native platform an EGL display is created on, for [EzglBuilder::egl_platform].
+///
+/// glutin picks the platform from the variant of the raw display handle, so a display handle for
+/// the wrong window system, e.g. an Xlib handle for a window shown through XWayland, gives a
+/// surface that never appears.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EglPlatform {
+    /// X11, from an Xlib or XCB display handle.
+    X11,
+    /// Wayland.
+    Wayland,
+    /// GBM, for drawing without a window system.
+    Gbm,
+}
+
+impl EglPlatform {
+    /// Get the platform glutin would choose for a display handle, if it's one of these.
+    pub fn from_display_handle(display_handle: RawDisplayHandle) -> Option<Self> {
+        match display_handle {
+            RawDisplayHandle::Xlib(_) | RawDisplayHandle::Xcb(_) => Some(EglPlatform::X11),
+            RawDisplayHandle::Wayland(_) => Some(EglPlatform::Wayland),
+            RawDisplayHandle::Gbm(_) => Some(EglPlatform::Gbm),
+            _ => None,
+        }
+    }
+}
+
+/// Export the symbols that ask the NVIDIA and AMD drivers on Windows to use the discrete GPU.
+///
+/// This must be invoked once at the top level of the binary crate, since the drivers only look at
+/// symbols exported from the executable. It does nothing on other platforms. On Linux, Mesa picks
+/// the GPU from the `DRI_PRIME` environment variable, which has to be set when the program is
+/// launched, and the proprietary NVIDIA driver from `__NV_PRIME_RENDER_OFFLOAD=1` and
+/// `__GLX_VENDOR_LIBRARY_NAME=nvidia`.
+///
+/// ```
+/// ezgl::prefer_high_performance_gpu!();
+/// # fn main() {}
+/// ```
+#[macro_export]
+macro_rules! prefer_high_performance_gpu {
+    () => {
+        #[cfg(windows)]
+        #[no_mangle]
+        #[used]
+        #[allow(non_upper_case_globals)]
+        pub static NvOptimusEnablement: u32 = 1;
+
+        #[cfg(windows)]
+        #[no_mangle]
+        #[used]
+        #[allow(non_upper_case_globals)]
+        pub static AmdPowerXpressRequestHighPerformance: u32 = 1;
+    };
+}
+
+/// Builder for [Ezgl], for setup options that the constructors don't cover.
+///
+/// ```no_run
+/// # #[cfg(feature = "winit")] {
+/// # let window: winit::window::Window = todo!();
+/// let ezgl = ezgl::Ezgl::builder()
+///     .prefer_samples(Some(4))
+///     .build_winit(&window)
+///     .unwrap();
+/// # }
+/// ```
+#[derive(Default)]
+pub struct EzglBuilder {
+    pub(crate) reg: Option<Reg>,
+    pub(crate) prefer_samples: Option<u8>,
+    pub(crate) debug_callback: Option<DebugCallback>,
+    pub(crate) create_retries: u32,
+    pub(crate) stereo: bool,
+    pub(crate) swap_behavior: SwapBehavior,
+    pub(crate) release_behavior: ReleaseBehavior,
+    pub(crate) gl_version: Option<(u8, u8)>,
+    pub(crate) check_errors: bool,
+    pub(crate) egl_platform: Option<EglPlatform>,
+    pub(crate) clear_color: [f32; 4],
+    pub(crate) prewarm: bool,
+    pub(crate) config_template:
+        Option<Box<dyn FnOnce(ConfigTemplateBuilder) -> ConfigTemplateBuilder>>,
+    pub(crate) config_filter: Option<ConfigFilter>,
+    pub(crate) context_attributes:
+        Option<Box<dyn FnOnce(ContextAttributesBuilder) -> ContextAttributesBuilder>>,
+}
+
+impl EzglBuilder {
+    /// Create a builder with the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the Xlib error hook registrar, which enables GLX on X11.
+    ///
+    /// With [EzglBuilder::build_winit] this defaults to winit's registrar.
+    pub fn xlib_error_hook(mut self, reg: Reg) -> Self {
+        self.reg = Some(reg);
+        self
+    }
+
+    /// Set the preferred number of samples.
+    ///
+    /// If None, the context configuration with the greatest number of sample buffers is preferred.
+    /// `Some(0)` disables multisampling: only single-sampled configurations are considered, and
+    /// setup returns [Error::Unsupported] if there are none. Other counts fall back to any
+    /// configuration if none has exactly that many samples.
+    pub fn prefer_samples(mut self, prefer_samples: Option<u8>) -> Self {
+        self.prefer_samples = prefer_samples;
+        self
+    }
+
+    /// Set the debug callback.
+    ///
+    /// The [HasContext::enable](crate::gl::HasContext::enable) function must be called with
+    /// [gl::DEBUG_OUTPUT](crate::gl::DEBUG_OUTPUT) to enable debug output.
+    pub fn debug_callback<F: FnMut(u32, u32, u32, u32, &str) + 'static>(
+        mut self,
+        debug_callback: F,
+    ) -> Self {
+        self.debug_callback = Some(Box::new(debug_callback));
+        self
+    }
+
+    /// Request a stereo (quad-buffered) configuration, with separate left and right back buffers.
+    ///
+    /// Setup returns [Error::Unsupported] if no stereo configuration is available. Select the
+    /// buffer to draw into with [EzglHandle::draw_buffer].
+    pub fn stereo(mut self, stereo: bool) -> Self {
+        self.stereo = stereo;
+        self
+    }
+
+    /// Set whether the back buffer is kept across swaps, through EGL's `EGL_SWAP_BEHAVIOR`.
+    ///
+    /// Defaults to [SwapBehavior::Destroyed], which leaves the surface as the driver created it.
+    /// Setup returns [Error::Unsupported] if [SwapBehavior::Preserved] is requested but the
+    /// context doesn't use EGL, or the configuration doesn't support preserving the buffer.
+    pub fn swap_behavior(mut self, swap_behavior: SwapBehavior) -> Self {
+        self.swap_behavior = swap_behavior;
+        self
+    }
+
+    /// Request a desktop GL version, e.g. `(4, 6)`.
+    ///
+    /// If the driver can't create a context with that version, each lower version of 4.6, 4.5,
+    /// 4.3, 4.1, and 3.3 is tried in turn, and then GLES. Check which version was created with
+    /// [HasContext::version](crate::gl::HasContext::version). By default no version is requested,
+    /// and the driver picks one before falling back to GLES.
+    pub fn gl_version(mut self, major: u8, minor: u8) -> Self {
+        self.gl_version = Some((major, minor));
+        self
+    }
+
+    /// Set whether the context is flushed when it stops being current.
+    ///
+    /// Defaults to [ReleaseBehavior::Flush]. [ReleaseBehavior::None] skips the implicit flush, which
+    /// makes switching contexts cheaper, but commands that haven't been flushed may never run. It
+    /// needs `KHR_context_flush_control` or the GLX/WGL equivalent, and setup fails without it.
+    ///
+    /// The behavior is fixed when the context is created, since glutin has no way to change it
+    /// afterward. Shared contexts from [Ezgl::new_shared_headless] are created with the same
+    /// behavior.
+    pub fn release_behavior(mut self, release_behavior: ReleaseBehavior) -> Self {
+        self.release_behavior = release_behavior;
+        self
+    }
+
+    /// Check for GL errors after each call ezgl's helpers make, in debug builds.
+    ///
+    /// Every ezgl helper and wrapper method that changes GL state or issues commands, like
+    /// [EzglHandle::draw_fullscreen_triangle], texture and buffer uploads, and render target setup,
+    /// then panics with the name of the helper if GL records an error, rather than it surfacing
+    /// later somewhere unrelated. Read-only queries like [EzglHandle::profile] and the `Drop`
+    /// impls of the wrapper types don't check. An error left behind by an earlier raw GL call is
+    /// reported by the next helper. The checks compile out in release builds. Defaults to false.
+    pub fn check_errors_in_debug(mut self, check_errors: bool) -> Self {
+        self.check_errors = check_errors;
+        self
+    }
+
+    /// Require EGL on a particular platform.
+    ///
+    /// GLX is skipped even if an [Xlib error hook](EzglBuilder::xlib_error_hook) is set, and setup
+    /// returns [Error::Unsupported] if the window's display handle is for a different platform,
+    /// rather than creating a surface that never shows up. Check which platform was used with
+    /// [Ezgl::egl_platform].
+    pub fn egl_platform(mut self, egl_platform: EglPlatform) -> Self {
+        self.egl_platform = Some(egl_platform);
+        self
+    }
+
+    /// Set the GL clear color right after the context is created.
+    ///
+    /// Saves a separate unsafe [HasContext::clear_color](crate::gl::HasContext::clear_color) call
+    /// before the first frame. Defaults to transparent black, which is GL's default.
+    pub fn clear_color(mut self, clear_color: [f32; 4]) -> Self {
+        self.clear_color = clear_color;
+        self
+    }
+
+    /// Compile a trivial program and draw with it once during setup.
+    ///
+    /// Many drivers initialize their shader compiler and pipeline lazily, so the first real draw
+    /// call hitches. Prewarming moves that cost into setup, which is slow anyway. This is a
+    /// best-effort latency optimization: it has no effect on rendering, and setup succeeds even
+    /// if the prewarm draw fails. Defaults to false.
+    pub fn prewarm(mut self, prewarm: bool) -> Self {
+        self.prewarm = prewarm;
+        self
+    }
+
+    /// Customize the template that configurations are chosen with.
+    ///
+    /// The closure receives ezgl's default template, which asks for 8 alpha bits and stereo if
+    /// [EzglBuilder::stereo] is set, and can add anything else [ConfigTemplateBuilder] supports,
+    /// e.g. depth, stencil, float color buffers, or transparency. The template is then always made
+    /// compatible with the window and its surface type is set to
+    /// [WINDOW](ConfigSurfaceTypes::WINDOW), so a customization can't pick a configuration the
+    /// window surface can't be created with.
+    ///
+    /// ```no_run
+    /// # #[cfg(feature = "winit")] {
+    /// # let window: winit::window::Window = todo!();
+    /// let ezgl = ezgl::Ezgl::builder()
+    ///     .config_template(|template| template.with_depth_size(24).with_stencil_size(8))
+    ///     .build_winit(&window)
+    ///     .unwrap();
+    /// # }
+    /// ```
+    pub fn config_template<F: FnOnce(ConfigTemplateBuilder) -> ConfigTemplateBuilder + 'static>(
+        mut self,
+        config_template: F,
+    ) -> Self {
+        self.config_template = Some(Box::new(config_template));
+        self
+    }
+
+    /// Only consider configurations that `filter` accepts.
+    ///
+    /// The filter sees each raw [Config] that matched the [template](EzglBuilder::config_template),
+    /// so it can require attributes the template can't express, e.g. the exact X11 visual or
+    /// color buffer layout a video decoder needs for zero-copy interop. Of the accepted
+    /// configurations, the one with the preferred number of samples is chosen as usual. Setup
+    /// returns [Error::Unsupported] if the filter rejects every configuration. Check what was
+    /// chosen with [Ezgl::config].
+    ///
+    /// ```no_run
+    /// # #[cfg(feature = "winit")] {
+    /// # let window: winit::window::Window = todo!();
+    /// use glutin::config::{ColorBufferType, GlConfig};
+    ///
+    /// let rgb10 = ColorBufferType::Rgb { r_size: 10, g_size: 10, b_size: 10 };
+    /// let ezgl = ezgl::Ezgl::builder()
+    ///     .config_filter(move |config| config.color_buffer_type() == Some(rgb10))
+    ///     .build_winit(&window)
+    ///     .unwrap();
+    /// # }
+    /// ```
+    pub fn config_filter<F: Fn(&Config) -> bool + 'static>(mut self, filter: F) -> Self {
+        self.config_filter = Some(Box::new(filter));
+        self
+    }
+
+    /// Customize the attributes the context is created with.
+    ///
+    /// The closure receives ezgl's default attributes, which have the
+    /// [release behavior](EzglBuilder::release_behavior) set, and can set anything else
+    /// [ContextAttributesBuilder] supports, e.g. robustness, debug, profile, or API version. If
+    /// [EzglBuilder::gl_version] is set, each version it tries replaces the API set here.
+    ///
+    /// If the context can't be created with these attributes, setup falls back to a GLES context
+    /// with glutin's defaults, which doesn't see this customization. Check what was created with
+    /// [HasContext::version](crate::gl::HasContext::version). A release behavior set here isn't
+    /// used for [shared contexts](Ezgl::new_shared_headless), so prefer
+    /// [EzglBuilder::release_behavior] for that.
+    ///
+    /// ```no_run
+    /// # #[cfg(feature = "winit")] {
+    /// # let window: winit::window::Window = todo!();
+    /// use glutin::context::Robustness;
+    ///
+    /// let ezgl = ezgl::Ezgl::builder()
+    ///     .context_attributes(|attributes| {
+    ///         attributes
+    ///             .with_debug(true)
+    ///             .with_robustness(Robustness::RobustLoseContextOnReset)
+    ///     })
+    ///     .build_winit(&window)
+    ///     .unwrap();
+    /// # }
+    /// ```
+    pub fn context_attributes<
+        F: FnOnce(ContextAttributesBuilder) -> ContextAttributesBuilder + 'static,
+    >(
+        mut self,
+        context_attributes: F,
+    ) -> Self {
+        self.context_attributes = Some(Box::new(context_attributes));
+        self
+    }
+
+    /// Set up ezgl with an existing [Window](winit::window::Window).
+    #[cfg(feature = "winit")]
+    pub fn build_winit(mut self, window: &winit::window::Window) -> Result<Ezgl> {
+        let winit::dpi::PhysicalSize { width, height } = window.inner_size();
+
+        #[cfg(unix)]
+        if self.reg.is_none() {
+            self.reg = Some(Box::new(winit::platform::x11::register_xlib_error_hook));
+        }
+
+        self.build(window, width, height)
+    }
+
+    /// Retry setup up to `retries` times if it fails.
+    ///
+    /// Some drivers intermittently fail to create a context. Display, config, surface, and context
+    /// creation are retried as a whole, with a short sleep between attempts, and the error from the
+    /// final attempt is returned. Defaults to 0, which makes a single attempt.
+    pub fn create_retries(mut self, retries: u32) -> Self {
+        self.create_retries = retries;
+        self
+    }
+
+    /// Set up ezgl.
+    ///
+    /// Requires a window that implements [HasRawWindowHandle] + [HasRawDisplayHandle].
+    pub fn build<H: HasRawWindowHandle + HasRawDisplayHandle>(
+        self,
+        window: &H,
+        width: u32,
+        height: u32,
+    ) -> Result<Ezgl> {
+        self.build_with_size(window, || {
+            (
+                NonZeroU32::new(width.max(1)).unwrap(),
+                NonZeroU32::new(height.max(1)).unwrap(),
+            )
+        })
+    }
+
+    /// Set up ezgl, asking for the size of the window only when the surface is created.
+    ///
+    /// `size` is called once, after the display and configuration are chosen and right before
+    /// the surface is created, so the size is sampled as late as possible. This avoids creating
+    /// the surface with a stale size when the window is still being mapped, e.g. on Wayland.
+    pub fn build_with_size<
+        H: HasRawWindowHandle + HasRawDisplayHandle,
+        F: FnOnce() -> (NonZeroU32, NonZeroU32),
+    >(
+        mut self,
+        window: &H,
+        size: F,
+    ) -> Result<Ezgl> {
+        let display_handle = window.raw_display_handle();
+        let window_handle = window.raw_window_handle();
+
+        let mut template = config_template(window_handle);
+        if self.stereo {
+            template = template.with_stereoscopy(Some(true));
+        }
+        if let Some(customize) = self.config_template.take() {
+            template = customize(template)
+                .compatible_with_native_window(window_handle)
+                .with_surface_type(ConfigSurfaceTypes::WINDOW);
+        }
+        let template = template.build();
+
+        let mut context_attributes =
+            ContextAttributesBuilder::new().with_release_behavior(self.release_behavior);
+        if let Some(customize) = self.context_attributes.take() {
+            context_attributes = customize(context_attributes);
+        }
+
+        // sampled on the first attempt that gets as far as creating the surface, and reused by
+        // any retries after that
+        let mut size = Some(size);
+        let mut cached_surface_attributes = None;
+        let mut surface_attributes = || {
+            cached_surface_attributes
+                .get_or_insert_with(|| {
+                    let (width, height) = size.take().unwrap()();
+                    surface_attributes(window_handle, width.get(), height.get())
+                })
+                .clone()
+        };
+
+        // the registrar is consumed by each attempt at display creation
+        let reg = self.reg.take().map(Rc::new);
+        let mut attempt = 0;
+        let (display, config, surface, glutin) = loop {
+            let reg = reg
+                .clone()
+                .map(|reg| Box::new(move |hook| reg(hook)) as Reg);
+            match self.create(
+                display_handle,
+                window_handle,
+                reg,
+                &template,
+                &context_attributes,
+                &mut surface_attributes,
+            ) {
+                Ok(parts) => break parts,
+                Err(_) if attempt < self.create_retries => {
+                    attempt += 1;
+                    std::thread::sleep(RETRY_DELAY);
+                }
+                Err(err) => return Err(err),
+            }
+        };
+
+        let mut glow = unsafe {
+            Context::from_loader_function(|symbol| {
+                let cstring = std::ffi::CString::new(symbol).unwrap();
+                display.get_proc_address(&cstring)
+            })
+        };
+
+        let debug_callback = install_debug_callback(
+            &mut glow,
+            self.debug_callback
+                .unwrap_or_else(|| Box::new(default_debug_callback)),
+        );
+
+        #[allow(clippy::arc_with_non_send_sync)]
+        let glow = Arc::new(glow);
+
+        let ezgl = Ezgl {
+            surface,
+            glutin,
+            handle: EzglHandle::new(glow, display, debug_callback, self.check_errors),
+            display_handle,
+            window_handle,
+            config,
+            swap_interval: Default::default(),
+            adaptive_vsync: Default::default(),
+            target_frame_time: Default::default(),
+            last_frame: Default::default(),
+            frame_count: Default::default(),
+            created: Instant::now(),
+            swap_behavior: self.swap_behavior,
+            release_behavior: self.release_behavior,
+            #[cfg(feature = "winit")]
+            window: None,
+        };
+        ezgl.apply_swap_behavior()?;
+
+        let [red, green, blue, alpha] = self.clear_color;
+        unsafe { ezgl.clear_color(red, green, blue, alpha) };
+
+        // only an optimization, so a failure isn't worth failing setup over
+        if self.prewarm {
+            let _ = ezgl.prewarm();
+        }
+
+        Ok(ezgl)
+    }
+
+    fn create(
+        &self,
+        display_handle: RawDisplayHandle,
+        window_handle: RawWindowHandle,
+        reg: Option<Reg>,
+        template: &ConfigTemplate,
+        context_attributes: &ContextAttributesBuilder,
+        surface_attributes: &mut dyn FnMut() -> SurfaceAttributes<WindowSurface>,
+    ) -> Result<(
+        Display,
+        Config,
+        Surface<WindowSurface>,
+        PossiblyCurrentContext,
+    )> {
+        let prefer_samples = self.prefer_samples;
+        let reg = match self.egl_platform {
+            Some(platform)
+                if EglPlatform::from_display_handle(display_handle) != Some(platform) =>
+            {
+                return Err(Error::Unsupported("EGL platform for the display handle"));
+            }
+            Some(_) => None,
+            None => reg,
+        };
+        let display = traced!(
+            "display",
+            create_display(display_handle, window_handle, reg)
+        )?;
+
+        let mut configs = unsafe {
+            traced!("find_configs", display.find_configs(template.clone()))?
+                .filter(|config| prefer_samples != Some(0) || config.num_samples() == 0)
+                .filter(|config| {
+                    self.config_filter
+                        .as_ref()
+                        .map_or(true, |filter| filter(config))
+                })
+                .collect::<Vec<_>>()
+        };
+
+        if configs.is_empty() {
+            if self.config_filter.is_some() {
+                return Err(Error::Unsupported("config matching the filter"));
+            } else if self.stereo {
+                return Err(Error::Unsupported("stereo config"));
+            } else if prefer_samples == Some(0) {
+                return Err(Error::Unsupported("single-sampled config"));
+            } else {
+                return Err(Error::Unsupported("config"));
+            }
+        }
+
+        // a config can pass the template but still not match the window's pixel format, which
+        // only shows up as a failure to create the surface, so fall back to the next best config
+        let surface_attributes = surface_attributes();
+        let (config, surface) = loop {
+            let config = configs.remove(select_config(&configs, prefer_samples));
+            match unsafe { display.create_window_surface(&config, &surface_attributes) } {
+                Ok(surface) => break (config, surface),
+                Err(_) if !configs.is_empty() => continue,
+                Err(err) => return Err(err.into()),
+            }
+        };
+
+        let desktop_versions = match self.gl_version {
+            Some((major, minor)) => std::iter::once((major, minor))
+                .chain(
+                    VERSION_LADDER
+                        .into_iter()
+                        .filter(|&version| version < (major, minor)),
+                )
+                .map(|(major, minor)| Some(Version::new(major, minor)))
+                .collect(),
+            None => vec![None],
+        };
+
+        let fallback_context_attributes = ContextAttributesBuilder::new()
+            .with_context_api(ContextApi::Gles(None))
+            .with_release_behavior(self.release_behavior)
+            .build(Some(window_handle));
+
+        let context = unsafe {
+            traced!(
+                "create_context",
+                desktop_versions
+                    .into_iter()
+                    .find_map(|version| {
+                        let mut context_attributes = context_attributes.clone();
+                        if version.is_some() {
+                            context_attributes =
+                                context_attributes.with_context_api(ContextApi::OpenGl(version));
+                        }
+                        let context_attributes = context_attributes.build(Some(window_handle));
+                        display.create_context(&config, &context_attributes).ok()
+                    })
+                    .map_or_else(
+                        || display.create_context(&config, &fallback_context_attributes),
+                        Ok,
+                    )
+            )?
+        };
+
+        let glutin = traced!("make_current", context.make_current(&surface))?;
+
+        Ok((display, config, surface, glutin))
+    }
+}
+
+/// Pick the index of the best config in a nonempty list.
+///
+/// With a preferred sample count, this is the last config with exactly that many samples, or the
+/// first config if there is none. Otherwise it is the first config with the most samples.
+fn select_config(configs: &[Config], prefer_samples: Option<u8>) -> usize {
+    (0..configs.len())
+        .reduce(|accum, i| {
+            if let Some(samples) = prefer_samples {
+                if configs[i].num_samples() == samples {
+                    i
+                } else {
+                    accum
+                }
+            } else {
+                if configs[i].num_samples() > configs[accum].num_samples() {
+                    i
+                } else {
+                    accum
+                }
+            }
+        })
+        .unwrap()
+}
@@ -0,0 +1,113 @@
+//! Compute shader dispatch.
+
+use crate::{
+    gl::{self, HasContext},
+    Error, EzglHandle, Result,
+};
+
+/// A set of barriers for [EzglHandle::memory_barrier].
+///
+/// Each barrier makes writes from shaders before it visible to a particular kind of read after
+/// it. Combine them with `|`.
+///
+/// ```
+/// # use ezgl::compute::MemoryBarrier;
+/// let barriers = MemoryBarrier::SHADER_STORAGE | MemoryBarrier::VERTEX_ATTRIB_ARRAY;
+/// assert!(barriers.contains(MemoryBarrier::SHADER_STORAGE));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MemoryBarrier(u32);
+
+impl MemoryBarrier {
+    /// Vertex attributes sourced from buffers.
+    pub const VERTEX_ATTRIB_ARRAY: Self = Self(gl::VERTEX_ATTRIB_ARRAY_BARRIER_BIT);
+    /// Indices sourced from the element buffer.
+    pub const ELEMENT_ARRAY: Self = Self(gl::ELEMENT_ARRAY_BARRIER_BIT);
+    /// Uniform blocks sourced from buffers.
+    pub const UNIFORM: Self = Self(gl::UNIFORM_BARRIER_BIT);
+    /// Texture sampling in shaders.
+    pub const TEXTURE_FETCH: Self = Self(gl::TEXTURE_FETCH_BARRIER_BIT);
+    /// Image loads and stores in shaders.
+    pub const SHADER_IMAGE_ACCESS: Self = Self(gl::SHADER_IMAGE_ACCESS_BARRIER_BIT);
+    /// Indirect draw and dispatch commands sourced from buffers.
+    pub const COMMAND: Self = Self(gl::COMMAND_BARRIER_BIT);
+    /// Pixel transfers to and from pixel buffers.
+    pub const PIXEL_BUFFER: Self = Self(gl::PIXEL_BUFFER_BARRIER_BIT);
+    /// Texture uploads and downloads.
+    pub const TEXTURE_UPDATE: Self = Self(gl::TEXTURE_UPDATE_BARRIER_BIT);
+    /// Buffer uploads, downloads, copies, and mappings.
+    pub const BUFFER_UPDATE: Self = Self(gl::BUFFER_UPDATE_BARRIER_BIT);
+    /// Framebuffer reads and writes.
+    pub const FRAMEBUFFER: Self = Self(gl::FRAMEBUFFER_BARRIER_BIT);
+    /// Transform feedback writes.
+    pub const TRANSFORM_FEEDBACK: Self = Self(gl::TRANSFORM_FEEDBACK_BARRIER_BIT);
+    /// Atomic counter buffer accesses.
+    pub const ATOMIC_COUNTER: Self = Self(gl::ATOMIC_COUNTER_BARRIER_BIT);
+    /// Shader storage buffer accesses.
+    pub const SHADER_STORAGE: Self = Self(gl::SHADER_STORAGE_BARRIER_BIT);
+    /// Every kind of access.
+    pub const ALL: Self = Self(gl::ALL_BARRIER_BITS);
+
+    /// Get the raw `GL_*_BARRIER_BIT` bits.
+    pub fn bits(self) -> u32 {
+        self.0
+    }
+
+    /// Check whether every barrier in `other` is also in this set.
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl std::ops::BitOr for MemoryBarrier {
+    type Output = Self;
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl std::ops::BitOrAssign for MemoryBarrier {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl EzglHandle {
+    pub(crate) fn supports_compute(&self) -> bool {
+        let version = self.version();
+        if version.is_embedded {
+            (version.major, version.minor) >= (3, 1)
+        } else {
+            (version.major, version.minor) >= (4, 3) || self.has_extension("GL_ARB_compute_shader")
+        }
+    }
+
+    /// Dispatch the current compute program with `x * y * z` work groups.
+    ///
+    /// Build the program with [EzglHandle::program_from_compute_source]. Returns
+    /// [Error::Unsupported] without GL 4.3, GLES 3.1, or `ARB_compute_shader`.
+    pub fn dispatch_compute(&self, x: u32, y: u32, z: u32) -> Result<()> {
+        if !self.supports_compute() {
+            return Err(Error::Unsupported("compute shaders"));
+        }
+
+        unsafe { HasContext::dispatch_compute(&**self, x, y, z) };
+        self.check_error("dispatch_compute");
+        Ok(())
+    }
+
+    /// Wait for shader writes to be visible to the kinds of reads in `barriers`, e.g. before
+    /// drawing with a buffer a compute shader wrote to.
+    ///
+    /// Returns [Error::Unsupported] where compute shaders are unsupported, as with
+    /// [EzglHandle::dispatch_compute].
+    pub fn memory_barrier(&self, barriers: MemoryBarrier) -> Result<()> {
+        if !self.supports_compute() {
+            return Err(Error::Unsupported("compute shaders"));
+        }
+
+        unsafe { HasContext::memory_barrier(&**self, barriers.bits()) };
+        self.check_error("memory_barrier");
+        Ok(())
+    }
+}
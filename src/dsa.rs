@@ -0,0 +1,257 @@
+//! Direct state access, for editing objects without binding them.
+
+use crate::{
+    gl::{self, HasContext, NativeBuffer, NativeTexture},
+    native_buffer, native_texture, EzglHandle, Result,
+};
+use std::ffi::c_void;
+
+type TextureStorage2D = unsafe extern "system" fn(u32, i32, u32, i32, i32);
+type NamedBufferData = unsafe extern "system" fn(u32, isize, *const c_void, u32);
+type MapNamedBufferRange = unsafe extern "system" fn(u32, isize, isize, u32) -> *mut c_void;
+type UnmapNamedBuffer = unsafe extern "system" fn(u32) -> u8;
+
+impl EzglHandle {
+    /// Check whether ezgl's texture and buffer helpers use direct state access.
+    ///
+    /// With GL 4.5 or `ARB_direct_state_access`, helpers like
+    /// [EzglHandle::create_texture_storage] and the typed buffers edit objects by name instead of
+    /// binding them, so they don't touch the bindings at all. Otherwise they bind the object and
+    /// restore the previous binding afterward. The check is made once per context and cached.
+    pub fn uses_dsa(&self) -> bool {
+        if let Some(uses_dsa) = self.uses_dsa.get() {
+            return uses_dsa;
+        }
+
+        let version = self.version();
+        let uses_dsa = !version.is_embedded
+            && ((version.major, version.minor) >= (4, 5)
+                || self.has_extension("GL_ARB_direct_state_access"));
+        self.uses_dsa.set(Some(uses_dsa));
+        uses_dsa
+    }
+
+    /// Create a texture. With direct state access it's created for `target` right away, since it
+    /// won't be bound before it's used.
+    pub(crate) fn create_texture_for_target(&self, target: u32) -> Result<NativeTexture> {
+        Ok(unsafe {
+            if self.uses_dsa() {
+                self.create_named_texture(target)?
+            } else {
+                self.create_texture()?
+            }
+        })
+    }
+
+    /// Set an integer parameter of a texture.
+    pub(crate) fn set_texture_parameter(
+        &self,
+        target: u32,
+        texture: NativeTexture,
+        name: u32,
+        value: i32,
+    ) {
+        if self.uses_dsa() {
+            unsafe { self.texture_parameter_i32(texture, name, value) };
+        } else {
+            self.with_texture(target, texture, || unsafe {
+                self.tex_parameter_i32(target, name, value)
+            });
+        }
+    }
+
+    /// Allocate immutable storage for a texture.
+    pub(crate) fn allocate_texture_storage_2d(
+        &self,
+        target: u32,
+        texture: NativeTexture,
+        levels: i32,
+        internal_format: u32,
+        width: i32,
+        height: i32,
+    ) {
+        if self.uses_dsa() {
+            if let Some(texture_storage_2d) = self.proc_address("glTextureStorage2D") {
+                let texture_storage_2d: TextureStorage2D =
+                    unsafe { std::mem::transmute(texture_storage_2d) };
+                unsafe {
+                    texture_storage_2d(texture.0.get(), levels, internal_format, width, height)
+                };
+                return;
+            }
+        }
+
+        self.with_texture(target, texture, || unsafe {
+            self.tex_storage_2d(target, levels, internal_format, width, height)
+        });
+    }
+
+    /// Create a buffer. With direct state access it's created with glCreateBuffers, since a name
+    /// from glGenBuffers can't be used until it has been bound.
+    pub(crate) fn create_buffer_object(&self) -> Result<NativeBuffer> {
+        Ok(unsafe {
+            if self.uses_dsa() {
+                self.create_named_buffer()?
+            } else {
+                self.create_buffer()?
+            }
+        })
+    }
+
+    /// Allocate `size` bytes of uninitialized storage for a buffer.
+    pub(crate) fn allocate_buffer(&self, target: u32, buffer: NativeBuffer, size: i32, usage: u32) {
+        if self.uses_dsa() {
+            // glow only wraps glNamedBufferData with data to upload
+            if let Some(named_buffer_data) = self.proc_address("glNamedBufferData") {
+                let named_buffer_data: NamedBufferData =
+                    unsafe { std::mem::transmute(named_buffer_data) };
+                unsafe {
+                    named_buffer_data(buffer.0.get(), size as isize, std::ptr::null(), usage)
+                };
+                return;
+            }
+        }
+
+        self.with_buffer(target, buffer, || unsafe {
+            self.buffer_data_size(target, size, usage)
+        });
+    }
+
+    /// Copy the start of a buffer into `data` by mapping it. Returns false if the buffer couldn't
+    /// be mapped.
+    pub(crate) fn copy_from_buffer(
+        &self,
+        target: u32,
+        buffer: NativeBuffer,
+        data: &mut [u8],
+    ) -> bool {
+        if self.uses_dsa() {
+            // glow doesn't wrap glMapNamedBufferRange or glUnmapNamedBuffer
+            if let Some((map, unmap)) = self
+                .proc_address("glMapNamedBufferRange")
+                .zip(self.proc_address("glUnmapNamedBuffer"))
+            {
+                let map: MapNamedBufferRange = unsafe { std::mem::transmute(map) };
+                let unmap: UnmapNamedBuffer = unsafe { std::mem::transmute(unmap) };
+                unsafe {
+                    let ptr = map(buffer.0.get(), 0, data.len() as isize, gl::MAP_READ_BIT);
+                    if ptr.is_null() {
+                        return false;
+                    }
+                    data.copy_from_slice(std::slice::from_raw_parts(ptr.cast(), data.len()));
+                    unmap(buffer.0.get());
+                }
+                return true;
+            }
+        }
+
+        let mut mapped = false;
+        self.with_buffer(target, buffer, || unsafe {
+            let ptr = self.map_buffer_range(target, 0, data.len() as i32, gl::MAP_READ_BIT);
+            if !ptr.is_null() {
+                data.copy_from_slice(std::slice::from_raw_parts(ptr, data.len()));
+                self.unmap_buffer(target);
+                mapped = true;
+            }
+        });
+        mapped
+    }
+
+    fn with_buffer<F: FnOnce()>(&self, target: u32, buffer: NativeBuffer, f: F) {
+        let binding = match target {
+            gl::ARRAY_BUFFER => gl::ARRAY_BUFFER_BINDING,
+            gl::UNIFORM_BUFFER => gl::UNIFORM_BUFFER_BINDING,
+            gl::SHADER_STORAGE_BUFFER => gl::SHADER_STORAGE_BUFFER_BINDING,
+            gl::DRAW_INDIRECT_BUFFER => gl::DRAW_INDIRECT_BUFFER_BINDING,
+            gl::PIXEL_PACK_BUFFER => gl::PIXEL_PACK_BUFFER_BINDING,
+            gl::PIXEL_UNPACK_BUFFER => gl::PIXEL_UNPACK_BUFFER_BINDING,
+            _ => panic!("Invalid buffer target {:#x}", target),
+        };
+
+        unsafe {
+            let prev = self.get_parameter_i32(binding);
+            self.bind_buffer(target, Some(buffer));
+            f();
+            self.bind_buffer(target, native_buffer(prev));
+        }
+    }
+
+    fn with_texture<F: FnOnce()>(&self, target: u32, texture: NativeTexture, f: F) {
+        let binding = match target {
+            gl::TEXTURE_2D => gl::TEXTURE_BINDING_2D,
+            gl::TEXTURE_CUBE_MAP => gl::TEXTURE_BINDING_CUBE_MAP,
+            gl::TEXTURE_1D_ARRAY => gl::TEXTURE_BINDING_1D_ARRAY,
+            gl::TEXTURE_RECTANGLE => gl::TEXTURE_BINDING_RECTANGLE,
+            gl::TEXTURE_2D_ARRAY => gl::TEXTURE_BINDING_2D_ARRAY,
+            gl::TEXTURE_3D => gl::TEXTURE_BINDING_3D,
+            _ => panic!("Invalid texture target {:#x}", target),
+        };
+
+        unsafe {
+            let prev = self.get_parameter_i32(binding);
+            self.bind_texture(target, Some(texture));
+            f();
+            self.bind_texture(target, native_texture(prev));
+        }
+    }
+}
+
+// only the types behind the bytemuck feature upload through these
+#[cfg(feature = "bytemuck")]
+mod buffer {
+    use crate::{
+        gl::{HasContext, NativeBuffer},
+        EzglHandle,
+    };
+    use std::ffi::c_void;
+
+    type NamedBufferSubData = unsafe extern "system" fn(u32, isize, isize, *const c_void);
+
+    impl EzglHandle {
+        /// Replace the contents of a buffer, reallocating it.
+        pub(crate) fn upload_buffer(
+            &self,
+            target: u32,
+            buffer: NativeBuffer,
+            data: &[u8],
+            usage: u32,
+        ) {
+            if self.uses_dsa() {
+                unsafe { self.named_buffer_data_u8_slice(buffer, data, usage) };
+            } else {
+                self.with_buffer(target, buffer, || unsafe {
+                    self.buffer_data_u8_slice(target, data, usage)
+                });
+            }
+        }
+
+        /// Overwrite part of a buffer, without reallocating it.
+        pub(crate) fn update_buffer(
+            &self,
+            target: u32,
+            buffer: NativeBuffer,
+            offset: i32,
+            data: &[u8],
+        ) {
+            if self.uses_dsa() {
+                if let Some(named_buffer_sub_data) = self.proc_address("glNamedBufferSubData") {
+                    let named_buffer_sub_data: NamedBufferSubData =
+                        unsafe { std::mem::transmute(named_buffer_sub_data) };
+                    unsafe {
+                        named_buffer_sub_data(
+                            buffer.0.get(),
+                            offset as isize,
+                            data.len() as isize,
+                            data.as_ptr().cast(),
+                        )
+                    };
+                    return;
+                }
+            }
+
+            self.with_buffer(target, buffer, || unsafe {
+                self.buffer_sub_data_u8_slice(target, offset, data)
+            });
+        }
+    }
+}
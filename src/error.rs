@@ -0,0 +1,67 @@
+//! Error type.
+
+/// Errors returned by ezgl.
+#[derive(Debug)]
+pub enum Error {
+    /// An error from glutin during setup or presentation.
+    Glutin(glutin::error::Error),
+
+    /// The window surface is no longer valid, and should be recreated with
+    /// [Ezgl::recreate_surface](crate::Ezgl::recreate_surface).
+    SurfaceLost(glutin::error::Error),
+
+    /// An error from GL while creating an object.
+    Gl(String),
+
+    /// A framebuffer was not complete. Contains the status from
+    /// [check_framebuffer_status](glow::HasContext::check_framebuffer_status).
+    IncompleteFramebuffer(u32),
+
+    /// The requested functionality is not supported by the context. Contains the name of the
+    /// missing extension or feature.
+    Unsupported(&'static str),
+}
+
+/// Result type returned by ezgl.
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Glutin(err) => write!(f, "{}", err),
+            Error::SurfaceLost(err) => write!(f, "Surface lost: {}", err),
+            Error::Gl(err) => write!(f, "GL error: {}", err),
+            Error::IncompleteFramebuffer(status) => {
+                write!(f, "Incomplete framebuffer: status {:#x}", status)
+            }
+            Error::Unsupported(what) => write!(f, "Unsupported: {}", what),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Glutin(err) | Error::SurfaceLost(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<glutin::error::Error> for Error {
+    fn from(err: glutin::error::Error) -> Self {
+        Error::Glutin(err)
+    }
+}
+
+impl From<String> for Error {
+    fn from(err: String) -> Self {
+        Error::Gl(err)
+    }
+}
+
+impl From<crate::shader::ProgramError> for Error {
+    fn from(err: crate::shader::ProgramError) -> Self {
+        Error::Gl(err.to_string())
+    }
+}
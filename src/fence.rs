@@ -0,0 +1,79 @@
+//! GPU sync fences.
+
+use crate::{
+    gl::{self, Context, HasContext, NativeFence},
+    EzglHandle, Result,
+};
+use std::{sync::Arc, time::Duration};
+
+/// The result of waiting on a [Fence].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FenceStatus {
+    /// The fence was already signaled when the wait started.
+    AlreadySignaled,
+    /// The fence was signaled during the wait.
+    Satisfied,
+    /// The timeout expired before the fence was signaled.
+    TimeoutExpired,
+    /// Waiting failed, e.g. because the context was lost.
+    Failed,
+}
+
+/// A sync fence, signaled once the GPU has finished all commands issued before it.
+///
+/// Unlike [finish](HasContext::finish), this lets the CPU wait on a specific point in the command
+/// stream, or just check whether it has been reached. The sync object is deleted when this is
+/// dropped.
+pub struct Fence {
+    glow: Arc<Context>,
+    fence: NativeFence,
+}
+
+impl Fence {
+    /// Insert a fence into the command stream.
+    pub fn insert(ezgl: &EzglHandle) -> Result<Self> {
+        let fence = unsafe { ezgl.fence_sync(gl::SYNC_GPU_COMMANDS_COMPLETE, 0)? };
+        ezgl.check_error("Fence::insert");
+        Ok(Self {
+            glow: ezgl.glow_context(),
+            fence,
+        })
+    }
+
+    /// Wait for the fence to be signaled, up to `timeout`.
+    ///
+    /// Pending commands are flushed first, so the wait can't deadlock. A zero timeout checks the
+    /// fence without blocking. GL limits the timeout to about 2.1 seconds; longer timeouts are
+    /// clamped.
+    pub fn wait(&self, ezgl: &EzglHandle, timeout: Duration) -> FenceStatus {
+        let timeout = timeout.as_nanos().min(i32::MAX as u128) as i32;
+        let status =
+            unsafe { ezgl.client_wait_sync(self.fence, gl::SYNC_FLUSH_COMMANDS_BIT, timeout) };
+        ezgl.check_error("Fence::wait");
+
+        match status {
+            gl::ALREADY_SIGNALED => FenceStatus::AlreadySignaled,
+            gl::CONDITION_SATISFIED => FenceStatus::Satisfied,
+            gl::TIMEOUT_EXPIRED => FenceStatus::TimeoutExpired,
+            _ => FenceStatus::Failed,
+        }
+    }
+
+    /// Check whether the fence has been signaled, without waiting or flushing.
+    pub fn is_signaled(&self, ezgl: &EzglHandle) -> bool {
+        let status = unsafe { ezgl.get_sync_status(self.fence) };
+        ezgl.check_error("Fence::is_signaled");
+        status == gl::SIGNALED
+    }
+
+    /// Get the raw GL sync object.
+    pub fn raw(&self) -> NativeFence {
+        self.fence
+    }
+}
+
+impl Drop for Fence {
+    fn drop(&mut self) {
+        unsafe { self.glow.delete_sync(self.fence) };
+    }
+}
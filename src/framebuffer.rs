@@ -0,0 +1,877 @@
+//! Offscreen render targets.
+
+use crate::{
+    gl::{self, Context, HasContext, NativeFramebuffer, NativeRenderbuffer, NativeTexture},
+    native_framebuffer, native_renderbuffer, native_texture,
+    texture::TextureParams,
+    Error, EzglHandle, Result,
+};
+use std::sync::Arc;
+
+/// A rectangle of pixels, with the origin at the bottom left.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Rect {
+    /// Create a rectangle.
+    pub fn new(x: i32, y: i32, width: i32, height: i32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+}
+
+/// How to filter when [blitting](EzglHandle::blit) between rectangles of different sizes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BlitFilter {
+    #[default]
+    Nearest,
+    Linear,
+}
+
+impl EzglHandle {
+    /// Copy a rectangle of pixels from one framebuffer to another, or None for the default
+    /// framebuffer.
+    ///
+    /// `mask` is some combination of `COLOR_BUFFER_BIT`, `DEPTH_BUFFER_BIT`, and
+    /// `STENCIL_BUFFER_BIT`. Depth and stencil can only be copied with [BlitFilter::Nearest], so
+    /// the filter is ignored if either is in the mask.
+    ///
+    /// If `read` is multisampled, the blit resolves it, which GL only allows if `src` and `dst`
+    /// are the same rectangle. Returns [Error::Gl] if they aren't. The previous read and draw
+    /// framebuffer bindings are restored afterward.
+    pub fn blit(
+        &self,
+        read: Option<NativeFramebuffer>,
+        draw: Option<NativeFramebuffer>,
+        src: Rect,
+        dst: Rect,
+        mask: u32,
+        filter: BlitFilter,
+    ) -> Result<()> {
+        let filter = if mask & (gl::DEPTH_BUFFER_BIT | gl::STENCIL_BUFFER_BIT) != 0 {
+            gl::NEAREST
+        } else {
+            match filter {
+                BlitFilter::Nearest => gl::NEAREST,
+                BlitFilter::Linear => gl::LINEAR,
+            }
+        };
+
+        unsafe {
+            let prev_draw = self.get_parameter_i32(gl::DRAW_FRAMEBUFFER_BINDING);
+            let prev_read = self.get_parameter_i32(gl::READ_FRAMEBUFFER_BINDING);
+
+            // SAMPLES is only queryable for the draw framebuffer
+            self.bind_framebuffer(gl::DRAW_FRAMEBUFFER, read);
+            let read_samples = self.get_parameter_i32(gl::SAMPLES);
+
+            let result = if read_samples > 0 && src != dst {
+                Err(Error::Gl(format!(
+                    "Multisample resolve needs matching rectangles, got {:?} and {:?}",
+                    src, dst
+                )))
+            } else {
+                self.bind_framebuffer(gl::READ_FRAMEBUFFER, read);
+                self.bind_framebuffer(gl::DRAW_FRAMEBUFFER, draw);
+                self.blit_framebuffer(
+                    src.x,
+                    src.y,
+                    src.x + src.width,
+                    src.y + src.height,
+                    dst.x,
+                    dst.y,
+                    dst.x + dst.width,
+                    dst.y + dst.height,
+                    mask,
+                    filter,
+                );
+                Ok(())
+            };
+
+            self.bind_framebuffer(gl::DRAW_FRAMEBUFFER, native_framebuffer(prev_draw));
+            self.bind_framebuffer(gl::READ_FRAMEBUFFER, native_framebuffer(prev_read));
+            self.check_error("blit");
+
+            result
+        }
+    }
+
+    /// Create a `DEPTH_COMPONENT24` renderbuffer, for a depth attachment that won't be sampled.
+    ///
+    /// A renderbuffer is cheaper than a depth texture when the depth values are only used for
+    /// depth testing. If `samples` is greater than 0 the storage is multisampled, to go with
+    /// multisampled color attachments. The previously bound renderbuffer is restored afterward.
+    pub fn create_depth_renderbuffer(
+        &self,
+        width: u32,
+        height: u32,
+        samples: u32,
+    ) -> Result<NativeRenderbuffer> {
+        unsafe {
+            let prev = self.get_parameter_i32(gl::RENDERBUFFER_BINDING);
+
+            let depth = self.create_renderbuffer()?;
+            self.bind_renderbuffer(gl::RENDERBUFFER, Some(depth));
+            if samples > 0 {
+                self.renderbuffer_storage_multisample(
+                    gl::RENDERBUFFER,
+                    samples as i32,
+                    gl::DEPTH_COMPONENT24,
+                    width as i32,
+                    height as i32,
+                );
+            } else {
+                self.renderbuffer_storage(
+                    gl::RENDERBUFFER,
+                    gl::DEPTH_COMPONENT24,
+                    width as i32,
+                    height as i32,
+                );
+            }
+            self.bind_renderbuffer(gl::RENDERBUFFER, native_renderbuffer(prev));
+            self.check_error("create_depth_renderbuffer");
+
+            Ok(depth)
+        }
+    }
+
+    /// Attach `num_views` consecutive layers of a 2D array texture, starting at `base_view`, for
+    /// rendering all of them in a single pass with `OVR_multiview`.
+    ///
+    /// Shaders select the layer with `gl_ViewID_OVR`. Every attachment of the framebuffer must
+    /// have the same number of views. Returns [Error::Unsupported] without `OVR_multiview`.
+    pub fn framebuffer_texture_multiview(
+        &self,
+        target: u32,
+        attachment: u32,
+        texture: NativeTexture,
+        level: i32,
+        base_view: i32,
+        num_views: i32,
+    ) -> Result<()> {
+        if !self.has_extension("GL_OVR_multiview") {
+            return Err(Error::Unsupported("OVR_multiview"));
+        }
+
+        type FramebufferTextureMultiview = unsafe extern "system" fn(u32, u32, u32, i32, i32, i32);
+        let framebuffer_texture_multiview: FramebufferTextureMultiview = unsafe {
+            std::mem::transmute(
+                self.proc_address("glFramebufferTextureMultiviewOVR")
+                    .ok_or(Error::Unsupported("glFramebufferTextureMultiviewOVR"))?,
+            )
+        };
+
+        unsafe {
+            framebuffer_texture_multiview(
+                target,
+                attachment,
+                texture.0.get(),
+                level,
+                base_view,
+                num_views,
+            )
+        };
+        self.check_error("framebuffer_texture_multiview");
+
+        Ok(())
+    }
+}
+
+/// A framebuffer with color texture attachments, for rendering to a texture.
+///
+/// The color attachments are `RGBA8` textures with linear filtering, and the optional depth
+/// attachment is a `DEPTH_COMPONENT24` renderbuffer. A [multiview](RenderTarget::multiview)
+/// target instead has 2D array textures with one layer per view. The framebuffer, textures, and
+/// renderbuffer are deleted when this is dropped.
+pub struct RenderTarget {
+    glow: Arc<Context>,
+    framebuffer: NativeFramebuffer,
+    textures: Vec<NativeTexture>,
+    depth: Option<NativeRenderbuffer>,
+    depth_texture: Option<NativeTexture>,
+    width: u32,
+    height: u32,
+    views: u32,
+}
+
+impl RenderTarget {
+    /// Create a render target with a single color attachment.
+    ///
+    /// Returns [Error::IncompleteFramebuffer] if the resulting framebuffer is not complete. The
+    /// previously bound framebuffer, texture, and renderbuffer are restored afterward.
+    pub fn new(ezgl: &EzglHandle, width: u32, height: u32, with_depth: bool) -> Result<Self> {
+        Self::with_color_attachments(ezgl, width, height, 1, with_depth)
+    }
+
+    /// Create a render target with a single color attachment and an existing depth renderbuffer,
+    /// e.g. one from [EzglHandle::create_depth_renderbuffer].
+    ///
+    /// The render target takes ownership of the renderbuffer and deletes it when dropped, or right
+    /// away if creating the target fails. The color attachment isn't multisampled, so the
+    /// renderbuffer must not be either, or the framebuffer is incomplete and this returns
+    /// [Error::IncompleteFramebuffer].
+    pub fn with_depth_renderbuffer(
+        ezgl: &EzglHandle,
+        width: u32,
+        height: u32,
+        depth: NativeRenderbuffer,
+    ) -> Result<Self> {
+        Self::create(ezgl, width, height, 1, Some(depth))
+    }
+
+    /// Create a render target with multiple color attachments, for multiple render targets.
+    ///
+    /// The textures are attached to `COLOR_ATTACHMENT0` through `COLOR_ATTACHMENT0 + count - 1`,
+    /// and all of them are enabled as draw buffers. `count` must be at least 1 and at most
+    /// `MAX_COLOR_ATTACHMENTS`.
+    pub fn with_color_attachments(
+        ezgl: &EzglHandle,
+        width: u32,
+        height: u32,
+        count: u32,
+        with_depth: bool,
+    ) -> Result<Self> {
+        let depth = if with_depth {
+            Some(ezgl.create_depth_renderbuffer(width, height, 0)?)
+        } else {
+            None
+        };
+
+        Self::create(ezgl, width, height, count, depth)
+    }
+
+    fn create(
+        ezgl: &EzglHandle,
+        width: u32,
+        height: u32,
+        count: u32,
+        depth: Option<NativeRenderbuffer>,
+    ) -> Result<Self> {
+        assert!(
+            count > 0,
+            "Render target needs at least one color attachment"
+        );
+
+        unsafe {
+            let framebuffer = match ezgl.create_framebuffer() {
+                Ok(framebuffer) => framebuffer,
+                Err(err) => {
+                    if let Some(depth) = depth {
+                        ezgl.delete_renderbuffer(depth);
+                    }
+                    return Err(err.into());
+                }
+            };
+
+            let mut target = Self {
+                glow: ezgl.glow_context(),
+                framebuffer,
+                textures: Vec::with_capacity(count as usize),
+                depth,
+                depth_texture: None,
+                width,
+                height,
+                views: 1,
+            };
+
+            // create everything before binding anything, so that dropping the target is all the
+            // cleanup an early return needs
+            for _ in 0..count {
+                target.textures.push(ezgl.create_texture()?);
+            }
+
+            let prev_framebuffer = ezgl.get_parameter_i32(gl::FRAMEBUFFER_BINDING);
+            let prev_texture = ezgl.get_parameter_i32(gl::TEXTURE_BINDING_2D);
+
+            ezgl.bind_framebuffer(gl::FRAMEBUFFER, Some(target.framebuffer));
+
+            for (i, &texture) in target.textures.iter().enumerate() {
+                ezgl.bind_texture(gl::TEXTURE_2D, Some(texture));
+                ezgl.tex_image_2d(
+                    gl::TEXTURE_2D,
+                    0,
+                    gl::RGBA8 as i32,
+                    width as i32,
+                    height as i32,
+                    0,
+                    gl::RGBA,
+                    gl::UNSIGNED_BYTE,
+                    None,
+                );
+                ezgl.set_texture_params(gl::TEXTURE_2D, TextureParams::default());
+                ezgl.framebuffer_texture_2d(
+                    gl::FRAMEBUFFER,
+                    gl::COLOR_ATTACHMENT0 + i as u32,
+                    gl::TEXTURE_2D,
+                    Some(texture),
+                    0,
+                );
+            }
+
+            if count > 1 {
+                ezgl.set_draw_buffers(count);
+            }
+
+            if let Some(depth) = depth {
+                ezgl.framebuffer_renderbuffer(
+                    gl::FRAMEBUFFER,
+                    gl::DEPTH_ATTACHMENT,
+                    gl::RENDERBUFFER,
+                    Some(depth),
+                );
+            }
+
+            let status = ezgl.check_framebuffer_status(gl::FRAMEBUFFER);
+
+            ezgl.bind_framebuffer(gl::FRAMEBUFFER, native_framebuffer(prev_framebuffer));
+            ezgl.bind_texture(gl::TEXTURE_2D, native_texture(prev_texture));
+            ezgl.check_error("RenderTarget::new");
+
+            if status != gl::FRAMEBUFFER_COMPLETE {
+                return Err(Error::IncompleteFramebuffer(status));
+            }
+
+            Ok(target)
+        }
+    }
+
+    /// Create a multiview render target, for rendering `views` views (e.g. one per eye) in a
+    /// single pass with `OVR_multiview`.
+    ///
+    /// The color attachment is an `RGBA8` 2D array texture with one layer per view. The optional
+    /// depth attachment is a `DEPTH_COMPONENT24` 2D array texture rather than a renderbuffer,
+    /// since every attachment needs the same number of views. Returns [Error::Unsupported]
+    /// without `OVR_multiview`, and [Error::IncompleteFramebuffer] if the framebuffer is not
+    /// complete. The previously bound framebuffer and array texture are restored afterward.
+    pub fn multiview(
+        ezgl: &EzglHandle,
+        width: u32,
+        height: u32,
+        views: u32,
+        with_depth: bool,
+    ) -> Result<Self> {
+        if !ezgl.has_extension("GL_OVR_multiview") {
+            return Err(Error::Unsupported("OVR_multiview"));
+        }
+
+        unsafe {
+            let mut target = Self {
+                glow: ezgl.glow_context(),
+                framebuffer: ezgl.create_framebuffer()?,
+                textures: Vec::with_capacity(1),
+                depth: None,
+                depth_texture: None,
+                width,
+                height,
+                views,
+            };
+
+            target.textures.push(ezgl.create_texture()?);
+            if with_depth {
+                target.depth_texture = Some(ezgl.create_texture()?);
+            }
+
+            let prev_framebuffer = ezgl.get_parameter_i32(gl::FRAMEBUFFER_BINDING);
+            let prev_texture = ezgl.get_parameter_i32(gl::TEXTURE_BINDING_2D_ARRAY);
+
+            ezgl.bind_framebuffer(gl::FRAMEBUFFER, Some(target.framebuffer));
+
+            ezgl.bind_texture(gl::TEXTURE_2D_ARRAY, Some(target.textures[0]));
+            ezgl.tex_image_3d(
+                gl::TEXTURE_2D_ARRAY,
+                0,
+                gl::RGBA8 as i32,
+                width as i32,
+                height as i32,
+                views as i32,
+                0,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                None,
+            );
+            ezgl.set_texture_params(gl::TEXTURE_2D_ARRAY, TextureParams::default());
+            let mut result = ezgl.framebuffer_texture_multiview(
+                gl::FRAMEBUFFER,
+                gl::COLOR_ATTACHMENT0,
+                target.textures[0],
+                0,
+                0,
+                views as i32,
+            );
+
+            if let (Some(depth), Ok(())) = (target.depth_texture, &result) {
+                ezgl.bind_texture(gl::TEXTURE_2D_ARRAY, Some(depth));
+                ezgl.tex_image_3d(
+                    gl::TEXTURE_2D_ARRAY,
+                    0,
+                    gl::DEPTH_COMPONENT24 as i32,
+                    width as i32,
+                    height as i32,
+                    views as i32,
+                    0,
+                    gl::DEPTH_COMPONENT,
+                    gl::UNSIGNED_INT,
+                    None,
+                );
+                ezgl.tex_parameter_i32(
+                    gl::TEXTURE_2D_ARRAY,
+                    gl::TEXTURE_MIN_FILTER,
+                    gl::NEAREST as i32,
+                );
+                ezgl.tex_parameter_i32(
+                    gl::TEXTURE_2D_ARRAY,
+                    gl::TEXTURE_MAG_FILTER,
+                    gl::NEAREST as i32,
+                );
+                result = ezgl.framebuffer_texture_multiview(
+                    gl::FRAMEBUFFER,
+                    gl::DEPTH_ATTACHMENT,
+                    depth,
+                    0,
+                    0,
+                    views as i32,
+                );
+            }
+
+            let status = ezgl.check_framebuffer_status(gl::FRAMEBUFFER);
+
+            ezgl.bind_framebuffer(gl::FRAMEBUFFER, native_framebuffer(prev_framebuffer));
+            ezgl.bind_texture(gl::TEXTURE_2D_ARRAY, native_texture(prev_texture));
+            ezgl.check_error("RenderTarget::multiview");
+
+            result?;
+            if status != gl::FRAMEBUFFER_COMPLETE {
+                return Err(Error::IncompleteFramebuffer(status));
+            }
+
+            Ok(target)
+        }
+    }
+
+    /// Bind the render target as the current framebuffer.
+    ///
+    /// This does not change the GL viewport.
+    pub fn bind(&self, ezgl: &EzglHandle) {
+        unsafe { ezgl.bind_framebuffer(gl::FRAMEBUFFER, Some(self.framebuffer)) };
+        ezgl.check_error("RenderTarget::bind");
+    }
+
+    /// Get the first color texture.
+    pub fn texture(&self) -> NativeTexture {
+        self.textures[0]
+    }
+
+    /// Get all color textures, in attachment order.
+    pub fn textures(&self) -> &[NativeTexture] {
+        &self.textures
+    }
+
+    /// Get the depth renderbuffer, if the target was created with one.
+    pub fn depth(&self) -> Option<NativeRenderbuffer> {
+        self.depth
+    }
+
+    /// Get the depth array texture, if the target is multiview and was created with depth.
+    pub fn depth_texture(&self) -> Option<NativeTexture> {
+        self.depth_texture
+    }
+
+    /// Get the raw framebuffer object.
+    pub fn framebuffer(&self) -> NativeFramebuffer {
+        self.framebuffer
+    }
+
+    /// Get the width of the render target.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Get the height of the render target.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Get the number of views, which is 1 unless the target is multiview.
+    pub fn views(&self) -> u32 {
+        self.views
+    }
+}
+
+impl Drop for RenderTarget {
+    fn drop(&mut self) {
+        unsafe {
+            self.glow.delete_framebuffer(self.framebuffer);
+            for texture in self.textures.iter() {
+                self.glow.delete_texture(*texture);
+            }
+            if let Some(depth) = self.depth {
+                self.glow.delete_renderbuffer(depth);
+            }
+            if let Some(depth) = self.depth_texture {
+                self.glow.delete_texture(depth);
+            }
+        }
+    }
+}
+
+/// A multisampled framebuffer with color texture attachments, for antialiased rendering that is
+/// resolved into single-sampled framebuffers afterward.
+///
+/// The color attachments are `RGBA8` multisampled textures, and the optional depth attachment is
+/// a multisampled `DEPTH_COMPONENT24` renderbuffer. With more than one color attachment, all of
+/// them are enabled as draw buffers, e.g. for a G-buffer. Multisampled textures need GL 3.2 or
+/// GLES 3.1. The framebuffer, textures, and renderbuffer are deleted when this is dropped.
+pub struct MsaaTarget {
+    glow: Arc<Context>,
+    framebuffer: NativeFramebuffer,
+    textures: Vec<NativeTexture>,
+    depth: Option<NativeRenderbuffer>,
+    width: u32,
+    height: u32,
+    samples: u32,
+}
+
+impl MsaaTarget {
+    /// Create a multisampled render target with `count` color attachments.
+    ///
+    /// `count` must be at least 1 and at most `MAX_COLOR_ATTACHMENTS`, and `samples` at most
+    /// `MAX_SAMPLES`. Returns [Error::Unsupported] below GLES 3.1, and
+    /// [Error::IncompleteFramebuffer] if the resulting framebuffer is not complete. The previously
+    /// bound framebuffer, multisampled texture, and renderbuffer are restored afterward.
+    pub fn new(
+        ezgl: &EzglHandle,
+        width: u32,
+        height: u32,
+        samples: u32,
+        count: u32,
+        with_depth: bool,
+    ) -> Result<Self> {
+        assert!(
+            count > 0,
+            "Render target needs at least one color attachment"
+        );
+
+        // GLES only has immutable multisampled textures, which desktop GL gained in 4.3
+        let version = ezgl.version();
+        if version.is_embedded && (version.major, version.minor) < (3, 1) {
+            return Err(Error::Unsupported("multisampled textures"));
+        }
+        let immutable = version.is_embedded || (version.major, version.minor) >= (4, 3);
+
+        let depth = if with_depth {
+            Some(ezgl.create_depth_renderbuffer(width, height, samples)?)
+        } else {
+            None
+        };
+
+        unsafe {
+            let framebuffer = match ezgl.create_framebuffer() {
+                Ok(framebuffer) => framebuffer,
+                Err(err) => {
+                    if let Some(depth) = depth {
+                        ezgl.delete_renderbuffer(depth);
+                    }
+                    return Err(err.into());
+                }
+            };
+
+            let mut target = Self {
+                glow: ezgl.glow_context(),
+                framebuffer,
+                textures: Vec::with_capacity(count as usize),
+                depth,
+                width,
+                height,
+                samples,
+            };
+
+            for _ in 0..count {
+                target.textures.push(ezgl.create_texture()?);
+            }
+
+            let prev_framebuffer = ezgl.get_parameter_i32(gl::FRAMEBUFFER_BINDING);
+            let prev_texture = ezgl.get_parameter_i32(gl::TEXTURE_BINDING_2D_MULTISAMPLE);
+
+            ezgl.bind_framebuffer(gl::FRAMEBUFFER, Some(target.framebuffer));
+
+            for (i, &texture) in target.textures.iter().enumerate() {
+                // fixed sample locations are required to mix textures with renderbuffers
+                ezgl.bind_texture(gl::TEXTURE_2D_MULTISAMPLE, Some(texture));
+                if immutable {
+                    ezgl.tex_storage_2d_multisample(
+                        gl::TEXTURE_2D_MULTISAMPLE,
+                        samples as i32,
+                        gl::RGBA8,
+                        width as i32,
+                        height as i32,
+                        true,
+                    );
+                } else {
+                    ezgl.tex_image_2d_multisample(
+                        gl::TEXTURE_2D_MULTISAMPLE,
+                        samples as i32,
+                        gl::RGBA8 as i32,
+                        width as i32,
+                        height as i32,
+                        true,
+                    );
+                }
+                ezgl.framebuffer_texture_2d(
+                    gl::FRAMEBUFFER,
+                    gl::COLOR_ATTACHMENT0 + i as u32,
+                    gl::TEXTURE_2D_MULTISAMPLE,
+                    Some(texture),
+                    0,
+                );
+            }
+
+            if count > 1 {
+                ezgl.set_draw_buffers(count);
+            }
+
+            if let Some(depth) = depth {
+                ezgl.framebuffer_renderbuffer(
+                    gl::FRAMEBUFFER,
+                    gl::DEPTH_ATTACHMENT,
+                    gl::RENDERBUFFER,
+                    Some(depth),
+                );
+            }
+
+            let status = ezgl.check_framebuffer_status(gl::FRAMEBUFFER);
+
+            ezgl.bind_framebuffer(gl::FRAMEBUFFER, native_framebuffer(prev_framebuffer));
+            ezgl.bind_texture(gl::TEXTURE_2D_MULTISAMPLE, native_texture(prev_texture));
+            ezgl.check_error("MsaaTarget::new");
+
+            if status != gl::FRAMEBUFFER_COMPLETE {
+                return Err(Error::IncompleteFramebuffer(status));
+            }
+
+            Ok(target)
+        }
+    }
+
+    /// Resolve each color attachment into a single-sampled framebuffer.
+    ///
+    /// Attachment `n` is blitted to the current draw buffer of `targets[n]`, e.g. the texture of a
+    /// [RenderTarget] with one color attachment, so `targets` must have one framebuffer per color
+    /// attachment. The targets must be the same size as this. The previous framebuffer bindings
+    /// are restored afterward.
+    pub fn resolve_to(&self, ezgl: &EzglHandle, targets: &[NativeFramebuffer]) -> Result<()> {
+        assert_eq!(
+            targets.len(),
+            self.textures.len(),
+            "Need one resolve target per color attachment"
+        );
+
+        let rect = Rect::new(0, 0, self.width as i32, self.height as i32);
+        unsafe {
+            let prev_read = ezgl.get_parameter_i32(gl::READ_FRAMEBUFFER_BINDING);
+            ezgl.bind_framebuffer(gl::READ_FRAMEBUFFER, Some(self.framebuffer));
+
+            let mut result = Ok(());
+            for (i, target) in targets.iter().enumerate() {
+                ezgl.read_buffer(gl::COLOR_ATTACHMENT0 + i as u32);
+                result = ezgl.blit(
+                    Some(self.framebuffer),
+                    Some(*target),
+                    rect,
+                    rect,
+                    gl::COLOR_BUFFER_BIT,
+                    BlitFilter::Nearest,
+                );
+                if result.is_err() {
+                    break;
+                }
+            }
+
+            ezgl.read_buffer(gl::COLOR_ATTACHMENT0);
+            ezgl.bind_framebuffer(gl::READ_FRAMEBUFFER, native_framebuffer(prev_read));
+            ezgl.check_error("MsaaTarget::resolve_to");
+            result
+        }
+    }
+
+    /// Bind the render target as the current framebuffer.
+    ///
+    /// This does not change the GL viewport.
+    pub fn bind(&self, ezgl: &EzglHandle) {
+        unsafe { ezgl.bind_framebuffer(gl::FRAMEBUFFER, Some(self.framebuffer)) };
+        ezgl.check_error("MsaaTarget::bind");
+    }
+
+    /// Get all multisampled color textures, in attachment order.
+    pub fn textures(&self) -> &[NativeTexture] {
+        &self.textures
+    }
+
+    /// Get the multisampled depth renderbuffer, if the target was created with one.
+    pub fn depth(&self) -> Option<NativeRenderbuffer> {
+        self.depth
+    }
+
+    /// Get the raw framebuffer object.
+    pub fn framebuffer(&self) -> NativeFramebuffer {
+        self.framebuffer
+    }
+
+    /// Get the number of samples per pixel.
+    pub fn samples(&self) -> u32 {
+        self.samples
+    }
+
+    /// Get the width of the render target.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Get the height of the render target.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+}
+
+impl Drop for MsaaTarget {
+    fn drop(&mut self) {
+        unsafe {
+            self.glow.delete_framebuffer(self.framebuffer);
+            for texture in self.textures.iter() {
+                self.glow.delete_texture(*texture);
+            }
+            if let Some(depth) = self.depth {
+                self.glow.delete_renderbuffer(depth);
+            }
+        }
+    }
+}
+
+/// A framebuffer with only a depth texture attachment, for depth-only passes like shadow maps.
+///
+/// The depth texture is `DEPTH_COMPONENT24` with nearest filtering, and can be sampled after
+/// rendering. The framebuffer and texture are deleted when this is dropped.
+pub struct DepthTarget {
+    glow: Arc<Context>,
+    framebuffer: NativeFramebuffer,
+    texture: NativeTexture,
+    width: u32,
+    height: u32,
+}
+
+impl DepthTarget {
+    /// Create a depth-only render target.
+    ///
+    /// The draw and read buffers are set to `NONE`, since a framebuffer without color attachments
+    /// is otherwise incomplete on some implementations. Returns [Error::IncompleteFramebuffer] if
+    /// the framebuffer is still not complete. The previously bound framebuffer and texture are
+    /// restored afterward.
+    pub fn new(ezgl: &EzglHandle, width: u32, height: u32) -> Result<Self> {
+        unsafe {
+            let framebuffer = ezgl.create_framebuffer()?;
+            let texture = match ezgl.create_texture() {
+                Ok(texture) => texture,
+                Err(err) => {
+                    ezgl.delete_framebuffer(framebuffer);
+                    return Err(err.into());
+                }
+            };
+
+            let target = Self {
+                glow: ezgl.glow_context(),
+                framebuffer,
+                texture,
+                width,
+                height,
+            };
+
+            let prev_framebuffer = ezgl.get_parameter_i32(gl::FRAMEBUFFER_BINDING);
+            let prev_texture = ezgl.get_parameter_i32(gl::TEXTURE_BINDING_2D);
+
+            ezgl.bind_texture(gl::TEXTURE_2D, Some(target.texture));
+            ezgl.tex_image_2d(
+                gl::TEXTURE_2D,
+                0,
+                gl::DEPTH_COMPONENT24 as i32,
+                width as i32,
+                height as i32,
+                0,
+                gl::DEPTH_COMPONENT,
+                gl::UNSIGNED_INT,
+                None,
+            );
+            ezgl.tex_parameter_i32(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as i32);
+            ezgl.tex_parameter_i32(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST as i32);
+            ezgl.tex_parameter_i32(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
+            ezgl.tex_parameter_i32(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
+
+            ezgl.bind_framebuffer(gl::FRAMEBUFFER, Some(target.framebuffer));
+            ezgl.framebuffer_texture_2d(
+                gl::FRAMEBUFFER,
+                gl::DEPTH_ATTACHMENT,
+                gl::TEXTURE_2D,
+                Some(target.texture),
+                0,
+            );
+            HasContext::draw_buffer(&**ezgl, gl::NONE);
+            ezgl.read_buffer(gl::NONE);
+
+            let status = ezgl.check_framebuffer_status(gl::FRAMEBUFFER);
+
+            ezgl.bind_framebuffer(gl::FRAMEBUFFER, native_framebuffer(prev_framebuffer));
+            ezgl.bind_texture(gl::TEXTURE_2D, native_texture(prev_texture));
+            ezgl.check_error("DepthTarget::new");
+
+            if status != gl::FRAMEBUFFER_COMPLETE {
+                return Err(Error::IncompleteFramebuffer(status));
+            }
+
+            Ok(target)
+        }
+    }
+
+    /// Bind the depth target as the current framebuffer.
+    ///
+    /// This does not change the GL viewport.
+    pub fn bind(&self, ezgl: &EzglHandle) {
+        unsafe { ezgl.bind_framebuffer(gl::FRAMEBUFFER, Some(self.framebuffer)) };
+        ezgl.check_error("DepthTarget::bind");
+    }
+
+    /// Get the depth texture.
+    pub fn texture(&self) -> NativeTexture {
+        self.texture
+    }
+
+    /// Get the raw framebuffer object.
+    pub fn framebuffer(&self) -> NativeFramebuffer {
+        self.framebuffer
+    }
+
+    /// Get the width of the depth target.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Get the height of the depth target.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+}
+
+impl Drop for DepthTarget {
+    fn drop(&mut self) {
+        unsafe {
+            self.glow.delete_framebuffer(self.framebuffer);
+            self.glow.delete_texture(self.texture);
+        }
+    }
+}
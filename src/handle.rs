@@ -0,0 +1,1140 @@
+//! Cloneable access to the GL context.
+
+use crate::{
+    gl::{self, Context, HasContext},
+    native_framebuffer, native_program, native_vertex_array, DebugCallbackSlot, Error, Result,
+};
+use glutin::display::{Display, GlDisplay};
+use std::{cell::Cell, rc::Rc, sync::Arc};
+
+/// A cloneable handle to the GL context of an [Ezgl](crate::Ezgl).
+///
+/// This has the helpers that only need the GL context, and can be handed out to subsystems
+/// instead of borrowing the [Ezgl](crate::Ezgl). It can't resize the surface or swap buffers, so
+/// the window surface still has a single owner. An [Ezgl](crate::Ezgl) derefs into its handle, so
+/// everything here is also available on it, and functions that take `&EzglHandle` accept
+/// `&Ezgl`.
+///
+/// This type implements Deref into [Context].
+#[derive(Clone)]
+pub struct EzglHandle {
+    pub(crate) glow: Arc<Context>,
+    pub(crate) display: Display,
+    cached: Rc<CachedObjects>,
+    pub(crate) uses_dsa: Rc<Cell<Option<bool>>>,
+    debug_callback: Rc<DebugCallbackSlot>,
+    #[cfg(feature = "state-cache")]
+    pub(crate) state_cache: Rc<crate::state_cache::StateCache>,
+    pub(crate) check_errors: bool,
+}
+
+impl EzglHandle {
+    pub(crate) fn new(
+        glow: Arc<Context>,
+        display: Display,
+        debug_callback: Rc<DebugCallbackSlot>,
+        check_errors: bool,
+    ) -> Self {
+        Self {
+            cached: Rc::new(CachedObjects {
+                glow: Arc::clone(&glow),
+                fullscreen_vao: Default::default(),
+                default_vao: Default::default(),
+                test_pattern_program: Default::default(),
+            }),
+            glow,
+            display,
+            uses_dsa: Default::default(),
+            debug_callback,
+            #[cfg(feature = "state-cache")]
+            state_cache: Default::default(),
+            check_errors,
+        }
+    }
+
+    /// Delete the objects the helpers created and cached, e.g. at shutdown while the context is
+    /// still current. They're created again if a helper needs them.
+    pub(crate) fn delete_cached_objects(&self) {
+        self.cached.delete();
+    }
+
+    /// Panic if GL has recorded an error, when error checking was enabled with
+    /// [EzglBuilder::check_errors_in_debug](crate::EzglBuilder::check_errors_in_debug).
+    ///
+    /// Helpers call this after their GL calls, with their own name as `call`. Does nothing in
+    /// release builds.
+    pub(crate) fn check_error(&self, call: &str) {
+        #[cfg(debug_assertions)]
+        if self.check_errors {
+            let error = unsafe { self.get_error() };
+            if error != gl::NO_ERROR {
+                panic!("GL error {:#x} in {}", error, call);
+            }
+        }
+
+        #[cfg(not(debug_assertions))]
+        let _ = (call, self.check_errors);
+    }
+
+    /// Check whether the context supports an extension.
+    ///
+    /// Extension names include the `GL_` prefix, e.g. `"GL_EXT_texture_sRGB_decode"`.
+    pub fn has_extension(&self, name: &str) -> bool {
+        self.supported_extensions().contains(name)
+    }
+
+    /// Check whether a GL function can be loaded, e.g. `"glMultiDrawArraysIndirect"`.
+    ///
+    /// Some drivers advertise an extension without exporting all of its functions, so check this
+    /// as well as [EzglHandle::has_extension] before calling an optional function.
+    pub fn has_extension_function(&self, symbol: &str) -> bool {
+        self.proc_address(symbol).is_some()
+    }
+
+    /// Append the GL vendor, renderer, and version, and the platform API, to panic messages.
+    ///
+    /// The strings are queried once now, while the context is current, so panics on threads where
+    /// it isn't current still report them. The previous panic hook runs first, and the context
+    /// info is printed to stderr after it. Each call wraps the hook again, so call this once.
+    pub fn install_panic_context(&self) {
+        let context = unsafe {
+            format!(
+                "GL context: {} {}, {} via {}",
+                self.get_parameter_string(gl::VENDOR),
+                self.get_parameter_string(gl::RENDERER),
+                self.get_parameter_string(gl::VERSION),
+                self.display.version_string(),
+            )
+        };
+
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            previous(info);
+            eprintln!("{}", context);
+        }));
+    }
+
+    /// Replace the debug callback.
+    ///
+    /// glow takes the callback by value when it's installed, so the context can't be given a new
+    /// one without mutable access. Instead, the callback installed during setup forwards each
+    /// message to a shared slot, and this replaces what's in the slot. It takes effect for every
+    /// clone of the handle. Debug output must still be enabled with
+    /// [gl::DEBUG_OUTPUT](crate::gl::DEBUG_OUTPUT).
+    ///
+    /// Messages raised while the callback is running, e.g. by GL calls made inside it, are
+    /// dropped. Calling this from inside the callback replaces the callback once it returns.
+    pub fn set_debug_callback<F: FnMut(u32, u32, u32, u32, &str) + 'static>(
+        &self,
+        debug_callback: F,
+    ) {
+        self.debug_callback.replace(Box::new(debug_callback));
+    }
+
+    /// Stop a single debug message from reaching the debug callback.
+    ///
+    /// Message IDs are only unique within a source and type, so those have to be given too, e.g.
+    /// `gl::DEBUG_SOURCE_API` and `gl::DEBUG_TYPE_OTHER`. Neither can be `DONT_CARE`.
+    pub fn mute_debug_message(&self, source: u32, type_: u32, id: u32) {
+        unsafe { self.debug_message_control(source, type_, gl::DONT_CARE, &[id], false) };
+        self.check_error("mute_debug_message");
+    }
+
+    /// Stop debug messages with any of these IDs from reaching the debug callback, whatever
+    /// their source and type.
+    ///
+    /// GL doesn't accept `DONT_CARE` for the source or type when IDs are given, so this mutes the
+    /// IDs for every combination of source and type.
+    pub fn mute_debug_messages(&self, ids: &[u32]) {
+        const SOURCES: [u32; 6] = [
+            gl::DEBUG_SOURCE_API,
+            gl::DEBUG_SOURCE_WINDOW_SYSTEM,
+            gl::DEBUG_SOURCE_SHADER_COMPILER,
+            gl::DEBUG_SOURCE_THIRD_PARTY,
+            gl::DEBUG_SOURCE_APPLICATION,
+            gl::DEBUG_SOURCE_OTHER,
+        ];
+        const TYPES: [u32; 9] = [
+            gl::DEBUG_TYPE_ERROR,
+            gl::DEBUG_TYPE_DEPRECATED_BEHAVIOR,
+            gl::DEBUG_TYPE_UNDEFINED_BEHAVIOR,
+            gl::DEBUG_TYPE_PORTABILITY,
+            gl::DEBUG_TYPE_PERFORMANCE,
+            gl::DEBUG_TYPE_OTHER,
+            gl::DEBUG_TYPE_MARKER,
+            gl::DEBUG_TYPE_PUSH_GROUP,
+            gl::DEBUG_TYPE_POP_GROUP,
+        ];
+
+        if ids.is_empty() {
+            return;
+        }
+
+        for source in SOURCES {
+            for type_ in TYPES {
+                unsafe { self.debug_message_control(source, type_, gl::DONT_CARE, ids, false) };
+            }
+        }
+        self.check_error("mute_debug_messages");
+    }
+
+    /// Annotate a point in the command stream, e.g. `"frame 120 start"`, for graphics debuggers.
+    ///
+    /// The message is inserted with `DEBUG_SOURCE_APPLICATION` and `DEBUG_TYPE_MARKER`, so it also
+    /// reaches the debug callback if debug output is enabled. Does nothing without GL 4.3, GLES
+    /// 3.2, or `KHR_debug`.
+    pub fn debug_marker(&self, message: &str) {
+        let version = self.version();
+        let supported = self.has_extension("GL_KHR_debug")
+            || if version.is_embedded {
+                (version.major, version.minor) >= (3, 2)
+            } else {
+                (version.major, version.minor) >= (4, 3)
+            };
+
+        if supported {
+            unsafe {
+                self.debug_message_insert(
+                    gl::DEBUG_SOURCE_APPLICATION,
+                    gl::DEBUG_TYPE_MARKER,
+                    0,
+                    gl::DEBUG_SEVERITY_NOTIFICATION,
+                    message,
+                )
+            };
+            self.check_error("debug_marker");
+        }
+    }
+
+    /// Enable or disable sRGB decoding when sampling a texture.
+    ///
+    /// When `decode` is false, an sRGB texture is sampled as-is rather than being converted to
+    /// linear. Requires `EXT_texture_sRGB_decode`, otherwise returns [Error::Unsupported]. Unless
+    /// [EzglHandle::uses_dsa], the texture is bound to `TEXTURE_2D` and the previous binding is
+    /// restored afterward.
+    pub fn set_texture_srgb_decode(&self, texture: gl::NativeTexture, decode: bool) -> Result<()> {
+        const TEXTURE_SRGB_DECODE_EXT: u32 = 0x8A48;
+        const DECODE_EXT: u32 = 0x8A49;
+        const SKIP_DECODE_EXT: u32 = 0x8A4A;
+
+        if !self.has_extension("GL_EXT_texture_sRGB_decode") {
+            return Err(Error::Unsupported("EXT_texture_sRGB_decode"));
+        }
+
+        self.set_texture_parameter(
+            gl::TEXTURE_2D,
+            texture,
+            TEXTURE_SRGB_DECODE_EXT,
+            if decode { DECODE_EXT } else { SKIP_DECODE_EXT } as i32,
+        );
+        self.check_error("set_texture_srgb_decode");
+
+        Ok(())
+    }
+
+    /// Set the clear color from sRGB-encoded components, like those from a color picker.
+    ///
+    /// [HasContext::clear_color] takes linear values, which are encoded to sRGB on write when the
+    /// surface is sRGB (ezgl requests one), so passing picker values directly gives a washed-out
+    /// color. This decodes each of `r`, `g`, and `b` first with the sRGB transfer function:
+    /// `c / 12.92` for `c <= 0.04045`, otherwise `((c + 0.055) / 1.055)^2.4`. Alpha is linear
+    /// and is passed through. Use the raw [HasContext::clear_color] for values that are already
+    /// linear, or when drawing to a framebuffer without sRGB encoding.
+    pub fn clear_color_srgb(&self, r: f32, g: f32, b: f32, a: f32) {
+        fn decode(c: f32) -> f32 {
+            if c <= 0.04045 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        }
+
+        unsafe { self.clear_color(decode(r), decode(g), decode(b), a) };
+        self.check_error("clear_color_srgb");
+    }
+
+    /// Bind a texture to a texture unit, e.g. the unit a sampler uniform is set to.
+    ///
+    /// `unit` is the index of the unit, not `TEXTURE0 + unit`. The previously active unit is made
+    /// active again afterward, so this doesn't disturb binds that assume a particular unit.
+    pub fn bind_texture_to_unit(&self, unit: u32, target: u32, texture: gl::NativeTexture) {
+        unsafe {
+            let prev = self.get_parameter_i32(gl::ACTIVE_TEXTURE) as u32;
+            self.active_texture(gl::TEXTURE0 + unit);
+            self.bind_texture(target, Some(texture));
+            self.active_texture(prev);
+        }
+        self.check_error("bind_texture_to_unit");
+
+        #[cfg(feature = "state-cache")]
+        self.state_cache
+            .set_texture(gl::TEXTURE0 + unit, target, Some(texture));
+    }
+
+    /// Get the index of the active texture unit, e.g. 0 for `TEXTURE0`.
+    pub fn active_texture_unit(&self) -> u32 {
+        unsafe { self.get_parameter_i32(gl::ACTIVE_TEXTURE) as u32 - gl::TEXTURE0 }
+    }
+
+    /// Enable or disable the scissor test.
+    pub fn set_scissor_enabled(&self, enabled: bool) {
+        unsafe {
+            if enabled {
+                self.enable(gl::SCISSOR_TEST);
+            } else {
+                self.disable(gl::SCISSOR_TEST);
+            }
+        }
+        self.check_error("set_scissor_enabled");
+    }
+
+    /// Enable or disable seamless filtering across cube map faces.
+    ///
+    /// Desktop GL samples each face separately by default, which shows seams at the edges of
+    /// environment maps. GLES 3.0 always filters seamlessly and has no `TEXTURE_CUBE_MAP_SEAMLESS`
+    /// enum, so this does nothing there.
+    pub fn set_seamless_cubemaps(&self, enabled: bool) {
+        if self.version().is_embedded {
+            return;
+        }
+
+        unsafe {
+            if enabled {
+                self.enable(gl::TEXTURE_CUBE_MAP_SEAMLESS);
+            } else {
+                self.disable(gl::TEXTURE_CUBE_MAP_SEAMLESS);
+            }
+        }
+        self.check_error("set_seamless_cubemaps");
+    }
+
+    /// Run GL commands with the scissor test enabled for a box.
+    ///
+    /// The previous scissor test state and scissor box are restored afterward.
+    pub fn with_scissor(&self, x: i32, y: i32, width: i32, height: i32, f: impl FnOnce()) {
+        unsafe {
+            let was_enabled = self.is_enabled(gl::SCISSOR_TEST);
+            let mut prev_box = [0; 4];
+            self.get_parameter_i32_slice(gl::SCISSOR_BOX, &mut prev_box);
+
+            self.enable(gl::SCISSOR_TEST);
+            self.scissor(x, y, width, height);
+
+            f();
+
+            self.scissor(prev_box[0], prev_box[1], prev_box[2], prev_box[3]);
+            self.set_scissor_enabled(was_enabled);
+        }
+        self.check_error("with_scissor");
+    }
+
+    /// Run GL commands with a framebuffer bound, or the default framebuffer if `framebuffer` is
+    /// None.
+    ///
+    /// The framebuffer is bound to `FRAMEBUFFER`, and the previous draw and read framebuffer
+    /// bindings are restored afterward.
+    pub fn with_framebuffer(&self, framebuffer: Option<gl::NativeFramebuffer>, f: impl FnOnce()) {
+        unsafe {
+            let prev_draw = self.get_parameter_i32(gl::DRAW_FRAMEBUFFER_BINDING);
+            let prev_read = self.get_parameter_i32(gl::READ_FRAMEBUFFER_BINDING);
+
+            self.bind_framebuffer(gl::FRAMEBUFFER, framebuffer);
+
+            f();
+
+            self.bind_framebuffer(gl::DRAW_FRAMEBUFFER, native_framebuffer(prev_draw));
+            self.bind_framebuffer(gl::READ_FRAMEBUFFER, native_framebuffer(prev_read));
+        }
+        self.check_error("with_framebuffer");
+    }
+
+    /// Read a single `RGBA8` pixel from the current read framebuffer as a packed ID, for picking.
+    ///
+    /// The channels are packed little-endian, so the ID is `r | g << 8 | b << 16 | a << 24`. Render
+    /// IDs to an offscreen target like a [RenderTarget](crate::framebuffer::RenderTarget) with
+    /// blending and multisampling off, since either would mix neighboring IDs. `y` counts from the
+    /// bottom of the framebuffer. Calls [HasContext::finish] first, so this stalls until the GPU
+    /// is done rendering.
+    pub fn read_pixel_u32(&self, x: i32, y: i32) -> u32 {
+        let mut pixel = [0u8; 4];
+        unsafe {
+            self.finish();
+
+            let prev_alignment = self.get_parameter_i32(gl::PACK_ALIGNMENT);
+            self.pixel_store_i32(gl::PACK_ALIGNMENT, 1);
+            self.read_pixels(
+                x,
+                y,
+                1,
+                1,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                gl::PixelPackData::Slice(&mut pixel),
+            );
+            self.pixel_store_i32(gl::PACK_ALIGNMENT, prev_alignment);
+        }
+        self.check_error("read_pixel_u32");
+
+        u32::from_le_bytes(pixel)
+    }
+
+    /// Read a rectangle of the depth buffer of the current read framebuffer, for picking or
+    /// debugging.
+    ///
+    /// Depths are in window space, from 0 at the near plane to 1 at the far plane, and rows go
+    /// from the bottom of the framebuffer up. The read framebuffer must have a depth buffer: the
+    /// default framebuffer only has one if the chosen configuration does, which can be checked
+    /// with [Ezgl::config_bits](crate::Ezgl::config_bits). GLES can't read depth at all without
+    /// `NV_read_depth`. Calls [HasContext::finish] first, so this stalls until the GPU is done
+    /// rendering.
+    pub fn read_depth(&self, x: i32, y: i32, width: u32, height: u32) -> Vec<f32> {
+        let mut bytes = vec![0u8; width as usize * height as usize * 4];
+        unsafe {
+            self.finish();
+            self.read_pixels(
+                x,
+                y,
+                width as i32,
+                height as i32,
+                gl::DEPTH_COMPONENT,
+                gl::FLOAT,
+                gl::PixelPackData::Slice(&mut bytes),
+            );
+        }
+        self.check_error("read_depth");
+
+        bytes
+            .chunks_exact(4)
+            .map(|depth| f32::from_ne_bytes(depth.try_into().unwrap()))
+            .collect()
+    }
+
+    /// Read a rectangle of the current read framebuffer into an existing buffer, so capture loops
+    /// can reuse one allocation.
+    ///
+    /// Rows are tightly packed and go from the bottom of the framebuffer up, so `dst` must be
+    /// exactly `width * height` pixels of `format` and `ty`, e.g. 4 bytes per pixel for `RGBA`
+    /// and `UNSIGNED_BYTE`. Returns [Error::Gl] if the length doesn't match or the combination of
+    /// `format` and `ty` isn't known. The previous pack alignment is restored afterward.
+    #[allow(clippy::too_many_arguments)]
+    pub fn read_pixels_into(
+        &self,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        format: u32,
+        ty: u32,
+        dst: &mut [u8],
+    ) -> Result<()> {
+        let pixel_size = pixel_size(format, ty).ok_or_else(|| {
+            Error::Gl(format!(
+                "Unknown pixel format {:#x} with type {:#x}",
+                format, ty
+            ))
+        })?;
+
+        let expected = width as usize * height as usize * pixel_size;
+        if dst.len() != expected {
+            return Err(Error::Gl(format!(
+                "Need {} bytes for {}x{} pixels, got {}",
+                expected,
+                width,
+                height,
+                dst.len()
+            )));
+        }
+
+        unsafe {
+            let prev_alignment = self.get_parameter_i32(gl::PACK_ALIGNMENT);
+            self.pixel_store_i32(gl::PACK_ALIGNMENT, 1);
+            self.read_pixels(
+                x,
+                y,
+                width as i32,
+                height as i32,
+                format,
+                ty,
+                gl::PixelPackData::Slice(dst),
+            );
+            self.pixel_store_i32(gl::PACK_ALIGNMENT, prev_alignment);
+        }
+        self.check_error("read_pixels_into");
+
+        Ok(())
+    }
+
+    /// Draw a single triangle that covers the whole viewport, for post-processing.
+    ///
+    /// No vertex data is bound, so the current program must compute positions from
+    /// `gl_VertexID`. An empty vertex array is created the first time this is called and reused
+    /// after, and the previous vertex array binding is restored afterward.
+    ///
+    /// ```glsl
+    /// out vec2 uv;
+    /// void main() {
+    ///     uv = vec2((gl_VertexID << 1) & 2, gl_VertexID & 2);
+    ///     gl_Position = vec4(uv * 2.0 - 1.0, 0.0, 1.0);
+    /// }
+    /// ```
+    pub fn draw_fullscreen_triangle(&self) -> Result<()> {
+        unsafe {
+            let vao = match self.cached.fullscreen_vao.get() {
+                Some(vao) => vao,
+                None => {
+                    let vao = self.create_vertex_array()?;
+                    self.cached.fullscreen_vao.set(Some(vao));
+                    vao
+                }
+            };
+
+            let prev = self.get_parameter_i32(gl::VERTEX_ARRAY_BINDING);
+            self.bind_vertex_array(Some(vao));
+            self.draw_arrays(gl::TRIANGLES, 0, 3);
+            self.bind_vertex_array(native_vertex_array(prev));
+        }
+        self.check_error("draw_fullscreen_triangle");
+
+        Ok(())
+    }
+
+    /// Draw a checkerboard over a color gradient to the default framebuffer, to check that the
+    /// context is alive and presenting.
+    ///
+    /// The pattern fills the current viewport, and doesn't depend on any of the app's shaders or
+    /// state: an internal program is compiled the first time this is called and reused after, and
+    /// depth and stencil testing, blending, face culling, and the scissor test are disabled while
+    /// drawing. The previous program, draw framebuffer binding, and capabilities are restored
+    /// afterward. Swap buffers to show the pattern.
+    pub fn draw_test_pattern(&self) -> Result<()> {
+        const CAPABILITIES: [u32; 5] = [
+            gl::DEPTH_TEST,
+            gl::STENCIL_TEST,
+            gl::BLEND,
+            gl::CULL_FACE,
+            gl::SCISSOR_TEST,
+        ];
+
+        let program = match self.cached.test_pattern_program.get() {
+            Some(program) => program,
+            None => {
+                let header = self.glsl_version_header();
+                let program = self.program_from_sources(
+                    &format!("{}{}", header, TEST_PATTERN_VERTEX),
+                    &format!("{}{}", header, TEST_PATTERN_FRAGMENT),
+                )?;
+                self.cached.test_pattern_program.set(Some(program));
+                program
+            }
+        };
+
+        unsafe {
+            let prev_program = self.get_parameter_i32(gl::CURRENT_PROGRAM);
+            let prev_framebuffer = self.get_parameter_i32(gl::DRAW_FRAMEBUFFER_BINDING);
+            let prev_capabilities = CAPABILITIES.map(|capability| self.is_enabled(capability));
+
+            self.bind_framebuffer(gl::DRAW_FRAMEBUFFER, None);
+            self.use_program(Some(program));
+            for capability in CAPABILITIES {
+                self.disable(capability);
+            }
+
+            let result = self.draw_fullscreen_triangle();
+
+            for (capability, enabled) in CAPABILITIES.into_iter().zip(prev_capabilities) {
+                if enabled {
+                    self.enable(capability);
+                }
+            }
+            self.use_program(native_program(prev_program));
+            self.bind_framebuffer(gl::DRAW_FRAMEBUFFER, native_framebuffer(prev_framebuffer));
+            self.check_error("draw_test_pattern");
+
+            result
+        }
+    }
+
+    /// Compile a trivial program and draw with it once into a 1x1 render target, so the driver
+    /// initializes its shader compiler and pipeline before the first real frame.
+    ///
+    /// Used by [EzglBuilder::prewarm](crate::EzglBuilder::prewarm). The previous program, draw
+    /// framebuffer binding, and viewport are restored afterward, and everything created is
+    /// deleted again.
+    pub(crate) fn prewarm(&self) -> Result<()> {
+        let header = self.glsl_version_header();
+        let program = self.program_from_sources(
+            &format!("{}{}", header, TEST_PATTERN_VERTEX),
+            &format!("{}{}", header, PREWARM_FRAGMENT),
+        )?;
+        let target = match crate::framebuffer::RenderTarget::new(self, 1, 1, false) {
+            Ok(target) => target,
+            Err(err) => {
+                unsafe { self.delete_program(program) };
+                return Err(err);
+            }
+        };
+
+        unsafe {
+            let prev_program = self.get_parameter_i32(gl::CURRENT_PROGRAM);
+            let prev_framebuffer = self.get_parameter_i32(gl::DRAW_FRAMEBUFFER_BINDING);
+            let mut prev_viewport = [0; 4];
+            self.get_parameter_i32_slice(gl::VIEWPORT, &mut prev_viewport);
+
+            self.bind_framebuffer(gl::DRAW_FRAMEBUFFER, Some(target.framebuffer()));
+            self.viewport(0, 0, 1, 1);
+            self.use_program(Some(program));
+
+            let result = self.draw_fullscreen_triangle();
+            self.finish();
+
+            let [x, y, width, height] = prev_viewport;
+            self.viewport(x, y, width, height);
+            self.use_program(native_program(prev_program));
+            self.bind_framebuffer(gl::DRAW_FRAMEBUFFER, native_framebuffer(prev_framebuffer));
+            self.delete_program(program);
+            self.check_error("prewarm");
+
+            result
+        }
+    }
+
+    /// Get the profile of the context.
+    ///
+    /// On GL 3.2 and later this reads `CONTEXT_PROFILE_MASK`. GL 3.1 has no profiles, but a
+    /// context without `GL_ARB_compatibility` has the deprecated features removed like a core
+    /// context. Contexts older than 3.1 are always compatibility. GLES has no profiles and gives
+    /// [GlProfile::Unknown].
+    pub fn profile(&self) -> GlProfile {
+        let version = self.version();
+        if version.is_embedded {
+            return GlProfile::Unknown;
+        }
+
+        match (version.major, version.minor) {
+            (major, minor) if (major, minor) >= (3, 2) => {
+                let mask = unsafe { self.get_parameter_i32(gl::CONTEXT_PROFILE_MASK) } as u32;
+                if mask & gl::CONTEXT_CORE_PROFILE_BIT != 0 {
+                    GlProfile::Core
+                } else if mask & gl::CONTEXT_COMPATIBILITY_PROFILE_BIT != 0 {
+                    GlProfile::Compatibility
+                } else {
+                    GlProfile::Unknown
+                }
+            }
+            (3, 1) if !self.has_extension("GL_ARB_compatibility") => GlProfile::Core,
+            _ => GlProfile::Compatibility,
+        }
+    }
+
+    /// Guess whether the context renders on the CPU, e.g. to turn off expensive effects.
+    ///
+    /// This is a heuristic: it looks for llvmpipe, softpipe, SwiftShader, or Microsoft Basic
+    /// Render in the `RENDERER` and `VENDOR` strings, so other software renderers aren't detected.
+    pub fn is_software_renderer(&self) -> bool {
+        const MARKERS: [&str; 4] = [
+            "llvmpipe",
+            "softpipe",
+            "swiftshader",
+            "microsoft basic render",
+        ];
+
+        let (renderer, vendor) = unsafe {
+            (
+                self.get_parameter_string(gl::RENDERER),
+                self.get_parameter_string(gl::VENDOR),
+            )
+        };
+        let strings = format!("{} {}", renderer, vendor).to_lowercase();
+        MARKERS.iter().any(|marker| strings.contains(marker))
+    }
+
+    /// Bind a vertex array if the context is core profile, so draws work without one.
+    ///
+    /// Core profile contexts have no default vertex array, and any draw call with vertex array
+    /// 0 bound fails with `INVALID_OPERATION`. This creates a vertex array the first time it's
+    /// called on a core context and binds it, replacing whatever was bound. On compatibility and
+    /// GLES contexts, which do have a default, it does nothing.
+    pub fn ensure_default_vao(&self) -> Result<()> {
+        if self.profile() != GlProfile::Core {
+            return Ok(());
+        }
+
+        let vao = match self.cached.default_vao.get() {
+            Some(vao) => vao,
+            None => {
+                let vao = unsafe { self.create_vertex_array()? };
+                self.cached.default_vao.set(Some(vao));
+                vao
+            }
+        };
+
+        unsafe { self.bind_vertex_array(Some(vao)) };
+        self.check_error("ensure_default_vao");
+
+        #[cfg(feature = "state-cache")]
+        self.state_cache.set_vertex_array(Some(vao));
+
+        Ok(())
+    }
+
+    /// Draw into the first `count` color attachments of the current framebuffer.
+    ///
+    /// Calls [HasContext::draw_buffers] with `COLOR_ATTACHMENT0` through
+    /// `COLOR_ATTACHMENT0 + count - 1`, so fragment shader output `n` goes to attachment `n`.
+    pub fn set_draw_buffers(&self, count: u32) {
+        let buffers = (0..count)
+            .map(|i| gl::COLOR_ATTACHMENT0 + i)
+            .collect::<Vec<_>>();
+        unsafe { self.draw_buffers(&buffers) };
+        self.check_error("set_draw_buffers");
+    }
+
+    /// Clear one color attachment of the current draw framebuffer that has an unsigned integer
+    /// format, e.g. `R32UI` for object IDs.
+    ///
+    /// `draw_buffer` is an index into the draw buffers, so with [EzglHandle::set_draw_buffers] it's
+    /// the attachment number. [HasContext::clear] gives undefined results for integer
+    /// attachments, so they must be cleared with the variant matching their format.
+    pub fn clear_color_attachment_u32(&self, draw_buffer: u32, value: [u32; 4]) {
+        unsafe { self.clear_buffer_u32_slice(gl::COLOR, draw_buffer, &value) };
+        self.check_error("clear_color_attachment_u32");
+    }
+
+    /// Clear one color attachment of the current draw framebuffer that has a signed integer
+    /// format. See [EzglHandle::clear_color_attachment_u32].
+    pub fn clear_color_attachment_i32(&self, draw_buffer: u32, value: [i32; 4]) {
+        unsafe { self.clear_buffer_i32_slice(gl::COLOR, draw_buffer, &value) };
+        self.check_error("clear_color_attachment_i32");
+    }
+
+    /// Clear one color attachment of the current draw framebuffer that has a float or normalized
+    /// format. See [EzglHandle::clear_color_attachment_u32].
+    pub fn clear_color_attachment_f32(&self, draw_buffer: u32, value: [f32; 4]) {
+        unsafe { self.clear_buffer_f32_slice(gl::COLOR, draw_buffer, &value) };
+        self.check_error("clear_color_attachment_f32");
+    }
+
+    /// Look up a GL function that glow doesn't wrap. Returns None if it isn't available.
+    pub(crate) fn proc_address(&self, name: &str) -> Option<*const std::ffi::c_void> {
+        let cstring = std::ffi::CString::new(name).unwrap();
+        let ptr = self.display.get_proc_address(&cstring);
+        (!ptr.is_null()).then_some(ptr)
+    }
+
+    /// Enable or disable antialiased lines, and set how hard the driver should try.
+    ///
+    /// Smoothed lines need blending to look right, e.g. with `SRC_ALPHA, ONE_MINUS_SRC_ALPHA`.
+    /// Returns [Error::Unsupported] on GLES, which doesn't have `LINE_SMOOTH`.
+    pub fn set_line_smooth(&self, enabled: bool, hint: QualityHint) -> Result<()> {
+        if self.version().is_embedded {
+            return Err(Error::Unsupported("LINE_SMOOTH"));
+        }
+
+        // glow doesn't wrap glHint
+        type Hint = unsafe extern "system" fn(u32, u32);
+        let gl_hint: Hint = unsafe {
+            std::mem::transmute(
+                self.proc_address("glHint")
+                    .ok_or(Error::Unsupported("glHint"))?,
+            )
+        };
+
+        let hint = match hint {
+            QualityHint::Fastest => gl::FASTEST,
+            QualityHint::Nicest => gl::NICEST,
+            QualityHint::DontCare => gl::DONT_CARE,
+        };
+
+        unsafe {
+            if enabled {
+                self.enable(gl::LINE_SMOOTH);
+            } else {
+                self.disable(gl::LINE_SMOOTH);
+            }
+            gl_hint(gl::LINE_SMOOTH_HINT, hint);
+        }
+        self.check_error("set_line_smooth");
+
+        Ok(())
+    }
+
+    /// Enable primitive restart at `index`, or disable it with None.
+    ///
+    /// While enabled, `index` in the index buffer of an indexed draw ends the current strip or fan
+    /// and starts a new one. Desktop GL enables `PRIMITIVE_RESTART` and can restart at any index.
+    /// GLES only has `PRIMITIVE_RESTART_FIXED_INDEX`, which always restarts at the greatest value
+    /// of the index type, so there `index` must be `u32::MAX`, `0xFFFF`, or `0xFF` to match the
+    /// index buffer. Returns [Error::Unsupported] without GLES 3.0.
+    pub fn set_primitive_restart(&self, index: Option<u32>) -> Result<()> {
+        let version = self.version();
+        if version.is_embedded {
+            if version.major < 3 {
+                return Err(Error::Unsupported("PRIMITIVE_RESTART_FIXED_INDEX"));
+            }
+            debug_assert!(
+                index.map_or(true, |index| [0xFF, 0xFFFF, u32::MAX].contains(&index)),
+                "GLES can only restart at the greatest value of the index type"
+            );
+
+            unsafe {
+                if index.is_some() {
+                    self.enable(gl::PRIMITIVE_RESTART_FIXED_INDEX);
+                } else {
+                    self.disable(gl::PRIMITIVE_RESTART_FIXED_INDEX);
+                }
+            }
+        } else if let Some(index) = index {
+            // glow doesn't wrap glPrimitiveRestartIndex
+            type PrimitiveRestartIndex = unsafe extern "system" fn(u32);
+            let primitive_restart_index: PrimitiveRestartIndex = unsafe {
+                std::mem::transmute(
+                    self.proc_address("glPrimitiveRestartIndex")
+                        .ok_or(Error::Unsupported("glPrimitiveRestartIndex"))?,
+                )
+            };
+
+            unsafe {
+                self.enable(gl::PRIMITIVE_RESTART);
+                primitive_restart_index(index);
+            }
+        } else {
+            unsafe { self.disable(gl::PRIMITIVE_RESTART) };
+        }
+        self.check_error("set_primitive_restart");
+
+        Ok(())
+    }
+
+    /// Set where the window origin is and what range of depths clip space maps to.
+    ///
+    /// [ClipDepth::ZeroToOne] gives better depth precision with reversed-Z, where the near plane
+    /// maps to depth 1 and the far plane to 0. Set it up by calling this with
+    /// [ClipOrigin::LowerLeft] and [ClipDepth::ZeroToOne], clearing depth to 0 with
+    /// [clear_depth_f32](HasContext::clear_depth_f32), setting the depth function to `GREATER`,
+    /// and using a projection matrix that maps near to 1 and far to 0. [ClipOrigin::UpperLeft]
+    /// flips the image vertically, e.g. to match Direct3D or Vulkan conventions.
+    ///
+    /// Returns [Error::Unsupported] without GL 4.5, `ARB_clip_control`, or `EXT_clip_control`.
+    pub fn set_clip_control(&self, origin: ClipOrigin, depth: ClipDepth) -> Result<()> {
+        let version = self.version();
+        let symbol = if !version.is_embedded
+            && ((version.major, version.minor) >= (4, 5)
+                || self.has_extension("GL_ARB_clip_control"))
+        {
+            "glClipControl"
+        } else if self.has_extension("GL_EXT_clip_control") {
+            "glClipControlEXT"
+        } else {
+            return Err(Error::Unsupported("ARB_clip_control"));
+        };
+
+        // glow doesn't wrap glClipControl
+        type ClipControl = unsafe extern "system" fn(u32, u32);
+        let clip_control: ClipControl = unsafe {
+            std::mem::transmute(
+                self.proc_address(symbol)
+                    .ok_or(Error::Unsupported("glClipControl"))?,
+            )
+        };
+
+        let origin = match origin {
+            ClipOrigin::LowerLeft => gl::LOWER_LEFT,
+            ClipOrigin::UpperLeft => gl::UPPER_LEFT,
+        };
+        let depth = match depth {
+            ClipDepth::NegativeOneToOne => gl::NEGATIVE_ONE_TO_ONE,
+            ClipDepth::ZeroToOne => gl::ZERO_TO_ONE,
+        };
+
+        unsafe { clip_control(origin, depth) };
+        self.check_error("set_clip_control");
+
+        Ok(())
+    }
+
+    /// Set up the usual state for 2D sprite or UI rendering, and get an orthographic projection.
+    ///
+    /// Depth testing is disabled, and blending is enabled with `FUNC_ADD` and `blend` as the blend
+    /// function. The projection maps `0..width` and `0..height` to the viewport with the origin at
+    /// the top left and y pointing down, in column-major order for
+    /// [uniform_matrix_4_f32_slice](HasContext::uniform_matrix_4_f32_slice) without transposing.
+    /// The state that was changed is saved in the returned [Setup2d], and put back with
+    /// [Setup2d::restore].
+    pub fn setup_2d(&self, width: u32, height: u32, blend: BlendMode) -> Setup2d {
+        let (src_rgb, dst_rgb, src_alpha, dst_alpha) = match blend {
+            BlendMode::Straight => (
+                gl::SRC_ALPHA,
+                gl::ONE_MINUS_SRC_ALPHA,
+                gl::ONE,
+                gl::ONE_MINUS_SRC_ALPHA,
+            ),
+            BlendMode::Premultiplied => (
+                gl::ONE,
+                gl::ONE_MINUS_SRC_ALPHA,
+                gl::ONE,
+                gl::ONE_MINUS_SRC_ALPHA,
+            ),
+        };
+
+        let saved = unsafe {
+            let saved = Saved2dState {
+                depth_test: self.is_enabled(gl::DEPTH_TEST),
+                blend: self.is_enabled(gl::BLEND),
+                blend_func: [
+                    self.get_parameter_i32(gl::BLEND_SRC_RGB) as u32,
+                    self.get_parameter_i32(gl::BLEND_DST_RGB) as u32,
+                    self.get_parameter_i32(gl::BLEND_SRC_ALPHA) as u32,
+                    self.get_parameter_i32(gl::BLEND_DST_ALPHA) as u32,
+                ],
+                blend_equation: [
+                    self.get_parameter_i32(gl::BLEND_EQUATION_RGB) as u32,
+                    self.get_parameter_i32(gl::BLEND_EQUATION_ALPHA) as u32,
+                ],
+            };
+
+            self.disable(gl::DEPTH_TEST);
+            self.enable(gl::BLEND);
+            self.blend_equation(gl::FUNC_ADD);
+            self.blend_func_separate(src_rgb, dst_rgb, src_alpha, dst_alpha);
+            saved
+        };
+        self.check_error("setup_2d");
+
+        let (x, y) = (2. / width.max(1) as f32, 2. / height.max(1) as f32);
+        #[rustfmt::skip]
+        let projection = [
+            x,   0.,  0., 0.,
+            0.,  -y,  0., 0.,
+            0.,  0., -1., 0.,
+            -1., 1.,  0., 1.,
+        ];
+
+        Setup2d { projection, saved }
+    }
+
+    /// Select which back buffer of a stereo context to draw into.
+    ///
+    /// The context must have been set up with [EzglBuilder::stereo](crate::EzglBuilder::stereo),
+    /// and the default framebuffer must be bound.
+    pub fn draw_buffer(&self, buffer: StereoBuffer) {
+        let buffer = match buffer {
+            StereoBuffer::Left => gl::BACK_LEFT,
+            StereoBuffer::Right => gl::BACK_RIGHT,
+        };
+        unsafe { HasContext::draw_buffer(&*self.glow, buffer) };
+        self.check_error("draw_buffer");
+    }
+
+    /// Increase the reference count of the inner glow [Context].
+    pub fn glow_context(&self) -> Arc<Context> {
+        Arc::clone(&self.glow)
+    }
+}
+
+/// Objects the helpers create the first time they need them, shared by every clone of a handle.
+///
+/// They're deleted when the last clone is dropped, or by [Ezgl::destroy](crate::Ezgl::destroy).
+struct CachedObjects {
+    glow: Arc<Context>,
+    fullscreen_vao: Cell<Option<gl::NativeVertexArray>>,
+    default_vao: Cell<Option<gl::NativeVertexArray>>,
+    test_pattern_program: Cell<Option<gl::NativeProgram>>,
+}
+
+impl CachedObjects {
+    fn delete(&self) {
+        unsafe {
+            if let Some(vao) = self.fullscreen_vao.take() {
+                self.glow.delete_vertex_array(vao);
+            }
+            if let Some(vao) = self.default_vao.take() {
+                self.glow.delete_vertex_array(vao);
+            }
+            // the program belongs to the share group, so it would outlive this context while a
+            // shared context exists
+            if let Some(program) = self.test_pattern_program.take() {
+                self.glow.delete_program(program);
+            }
+        }
+    }
+}
+
+impl Drop for CachedObjects {
+    fn drop(&mut self) {
+        self.delete();
+    }
+}
+
+impl std::ops::Deref for EzglHandle {
+    type Target = Context;
+    fn deref(&self) -> &Self::Target {
+        &self.glow
+    }
+}
+
+/// The profile of a GL context, from [EzglHandle::profile].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GlProfile {
+    /// Deprecated features like the default vertex array and wide lines are removed.
+    Core,
+    /// Deprecated features are still available.
+    Compatibility,
+    /// The profile couldn't be determined, e.g. on GLES.
+    Unknown,
+}
+
+/// How much quality to trade for speed, for [EzglHandle::set_line_smooth].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QualityHint {
+    /// The fastest option.
+    Fastest,
+    /// The highest quality option.
+    Nicest,
+    /// Let the driver decide.
+    #[default]
+    DontCare,
+}
+
+/// Where the window origin is, for [EzglHandle::set_clip_control].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ClipOrigin {
+    /// The bottom left, which is GL's default.
+    #[default]
+    LowerLeft,
+    /// The top left.
+    UpperLeft,
+}
+
+/// The range of depths clip space maps to, for [EzglHandle::set_clip_control].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ClipDepth {
+    /// -1 to 1, which is GL's default.
+    #[default]
+    NegativeOneToOne,
+    /// 0 to 1, as in Direct3D and Vulkan.
+    ZeroToOne,
+}
+
+/// How colors are blended with what's already drawn, for [EzglHandle::setup_2d].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BlendMode {
+    /// Colors are not multiplied by their alpha, as with most image files.
+    #[default]
+    Straight,
+    /// Colors are already multiplied by their alpha, which blends correctly when filtered.
+    Premultiplied,
+}
+
+/// The projection from [EzglHandle::setup_2d], and the state it changed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Setup2d {
+    /// The orthographic projection, in column-major order.
+    pub projection: [f32; 16],
+    saved: Saved2dState,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Saved2dState {
+    depth_test: bool,
+    blend: bool,
+    blend_func: [u32; 4],
+    blend_equation: [u32; 2],
+}
+
+impl Setup2d {
+    /// Put back the depth test, blending, blend function, and blend equation as they were before
+    /// [EzglHandle::setup_2d].
+    pub fn restore(self, ezgl: &EzglHandle) {
+        let Saved2dState {
+            depth_test,
+            blend,
+            blend_func: [src_rgb, dst_rgb, src_alpha, dst_alpha],
+            blend_equation: [equation_rgb, equation_alpha],
+        } = self.saved;
+
+        unsafe {
+            for (capability, enabled) in [(gl::DEPTH_TEST, depth_test), (gl::BLEND, blend)] {
+                if enabled {
+                    ezgl.enable(capability);
+                } else {
+                    ezgl.disable(capability);
+                }
+            }
+            ezgl.blend_equation_separate(equation_rgb, equation_alpha);
+            ezgl.blend_func_separate(src_rgb, dst_rgb, src_alpha, dst_alpha);
+        }
+        ezgl.check_error("Setup2d::restore");
+    }
+}
+
+/// A back buffer of a stereo context, for [EzglHandle::draw_buffer].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StereoBuffer {
+    /// The back buffer for the left eye.
+    Left,
+    /// The back buffer for the right eye.
+    Right,
+}
+
+/// Get the size in bytes of one pixel of a `read_pixels` format and type.
+fn pixel_size(format: u32, ty: u32) -> Option<usize> {
+    let packed = match ty {
+        gl::UNSIGNED_SHORT_5_6_5 | gl::UNSIGNED_SHORT_4_4_4_4 | gl::UNSIGNED_SHORT_5_5_5_1 => 2,
+        gl::UNSIGNED_INT_2_10_10_10_REV
+        | gl::UNSIGNED_INT_10F_11F_11F_REV
+        | gl::UNSIGNED_INT_5_9_9_9_REV
+        | gl::UNSIGNED_INT_24_8 => 4,
+        gl::FLOAT_32_UNSIGNED_INT_24_8_REV => 8,
+        _ => 0,
+    };
+    if packed > 0 {
+        return Some(packed);
+    }
+
+    let components = match format {
+        gl::RED
+        | gl::RED_INTEGER
+        | gl::GREEN
+        | gl::BLUE
+        | gl::ALPHA
+        | gl::DEPTH_COMPONENT
+        | gl::STENCIL_INDEX => 1,
+        gl::RG | gl::RG_INTEGER => 2,
+        gl::RGB | gl::RGB_INTEGER | gl::BGR => 3,
+        gl::RGBA | gl::RGBA_INTEGER | gl::BGRA => 4,
+        _ => return None,
+    };
+    let component_size = match ty {
+        gl::UNSIGNED_BYTE | gl::BYTE => 1,
+        gl::UNSIGNED_SHORT | gl::SHORT | gl::HALF_FLOAT => 2,
+        gl::UNSIGNED_INT | gl::INT | gl::FLOAT => 4,
+        _ => return None,
+    };
+
+    Some(components * component_size)
+}
+
+const TEST_PATTERN_VERTEX: &str = r"
+out vec2 uv;
+void main() {
+    uv = vec2((gl_VertexID << 1) & 2, gl_VertexID & 2);
+    gl_Position = vec4(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+";
+
+const TEST_PATTERN_FRAGMENT: &str = r"
+in vec2 uv;
+out vec4 color;
+void main() {
+    ivec2 cell = ivec2(gl_FragCoord.xy) / 32;
+    float shade = (cell.x + cell.y) % 2 == 0 ? 1.0 : 0.5;
+    color = vec4(vec3(uv, 1.0 - uv.x) * shade, 1.0);
+}
+";
+
+const PREWARM_FRAGMENT: &str = r"
+out vec4 color;
+void main() {
+    color = vec4(1.0);
+}
+";
@@ -0,0 +1,102 @@
+//! Multi-draw-indirect, for GPU-driven rendering.
+
+use crate::{
+    gl::{self, HasContext, NativeBuffer},
+    native_buffer, Error, EzglHandle, Result,
+};
+use std::ffi::c_void;
+
+type MultiDrawArraysIndirect = unsafe extern "system" fn(u32, *const c_void, i32, i32);
+type MultiDrawElementsIndirect = unsafe extern "system" fn(u32, u32, *const c_void, i32, i32);
+
+impl EzglHandle {
+    fn supports_multi_draw_indirect(&self) -> bool {
+        let version = self.version();
+        self.has_extension("GL_ARB_multi_draw_indirect")
+            || self.has_extension("GL_EXT_multi_draw_indirect")
+            || (!version.is_embedded && (version.major, version.minor) >= (4, 3))
+    }
+
+    /// Issue `draw_count` array draws with parameters read from `buffer`.
+    ///
+    /// Each command in the buffer is four `u32`s: `count`, `instance_count`, `first`, and
+    /// `base_instance`. `stride` is the distance in bytes between commands, or 0 if they are
+    /// tightly packed. Returns [Error::Unsupported] without GL 4.3 or `ARB_multi_draw_indirect`.
+    /// The buffer is bound to `DRAW_INDIRECT_BUFFER` and the previous binding is restored
+    /// afterward.
+    pub fn multi_draw_arrays_indirect(
+        &self,
+        mode: u32,
+        buffer: NativeBuffer,
+        draw_count: u32,
+        stride: u32,
+    ) -> Result<()> {
+        if !self.supports_multi_draw_indirect() {
+            return Err(Error::Unsupported("ARB_multi_draw_indirect"));
+        }
+
+        let multi_draw_arrays_indirect: MultiDrawArraysIndirect = unsafe {
+            std::mem::transmute(
+                self.proc_address("glMultiDrawArraysIndirect")
+                    .ok_or(Error::Unsupported("ARB_multi_draw_indirect"))?,
+            )
+        };
+
+        self.with_draw_indirect_buffer(buffer, || unsafe {
+            multi_draw_arrays_indirect(mode, std::ptr::null(), draw_count as i32, stride as i32)
+        });
+        self.check_error("multi_draw_arrays_indirect");
+
+        Ok(())
+    }
+
+    /// Issue `draw_count` indexed draws with parameters read from `buffer`, using the element
+    /// buffer of the current vertex array.
+    ///
+    /// Each command in the buffer is five `u32`s: `count`, `instance_count`, `first_index`,
+    /// `base_vertex`, and `base_instance`. `element_type` is the type of the indices, e.g.
+    /// `UNSIGNED_INT`. Otherwise this works like [EzglHandle::multi_draw_arrays_indirect].
+    pub fn multi_draw_elements_indirect(
+        &self,
+        mode: u32,
+        element_type: u32,
+        buffer: NativeBuffer,
+        draw_count: u32,
+        stride: u32,
+    ) -> Result<()> {
+        if !self.supports_multi_draw_indirect() {
+            return Err(Error::Unsupported("ARB_multi_draw_indirect"));
+        }
+
+        let multi_draw_elements_indirect: MultiDrawElementsIndirect = unsafe {
+            std::mem::transmute(
+                self.proc_address("glMultiDrawElementsIndirect")
+                    .ok_or(Error::Unsupported("ARB_multi_draw_indirect"))?,
+            )
+        };
+
+        self.with_draw_indirect_buffer(buffer, || unsafe {
+            multi_draw_elements_indirect(
+                mode,
+                element_type,
+                std::ptr::null(),
+                draw_count as i32,
+                stride as i32,
+            )
+        });
+        self.check_error("multi_draw_elements_indirect");
+
+        Ok(())
+    }
+
+    fn with_draw_indirect_buffer(&self, buffer: NativeBuffer, f: impl FnOnce()) {
+        unsafe {
+            let prev = self.get_parameter_i32(gl::DRAW_INDIRECT_BUFFER_BINDING);
+            self.bind_buffer(gl::DRAW_INDIRECT_BUFFER, Some(buffer));
+
+            f();
+
+            self.bind_buffer(gl::DRAW_INDIRECT_BUFFER, native_buffer(prev));
+        }
+    }
+}
@@ -1,7 +1,21 @@
 //! Easy GL setup via [glutin]/[glow] for the user who doesn't care how they get their context.
 //!
 //! This crate reexports [glow] as `gl`, as well as [glutin] and [raw_window_handle]. Additionally
-//! [winit](docs.rs/winit) is available if `feature = "winit"` is enabled.
+//! [winit](docs.rs/winit) is available if `feature = "winit"` is enabled, which it is by default.
+//!
+//! With `feature = "tracing"`, context setup is instrumented with [tracing](docs.rs/tracing)
+//! spans, and the default debug callback emits tracing events instead of printing.
+//!
+//! With `feature = "bytemuck"`, the `uniform` and `storage` modules provide typed uniform and
+//! shader storage buffers, and the `mesh` module provides indexed meshes.
+//!
+//! With `feature = "notify"`, the `watch` module reloads shaders when their files change.
+//!
+//! ezgl always creates its own context, and can't adopt one that a host application (e.g. SDL or
+//! a video SDK) already created. To draw into a context like that, make it current and build a
+//! glow context against it with
+//! [Context::from_loader_function](gl::Context::from_loader_function), using the host's
+//! `eglGetProcAddress` or equivalent as the loader.
 
 pub use glow as gl;
 pub use glutin;
@@ -10,75 +24,198 @@ pub use raw_window_handle;
 #[cfg(feature = "winit")]
 pub use winit;
 
+/// Evaluate an expression inside a tracing span if `feature = "tracing"` is enabled.
+macro_rules! traced {
+    ($name:literal, $body:expr) => {{
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!($name).entered();
+        $body
+    }};
+}
+
+#[cfg(feature = "winit")]
+pub mod app;
+mod builder;
+pub mod compute;
+mod dsa;
+mod error;
+pub mod fence;
+pub mod framebuffer;
+mod handle;
+mod indirect;
+#[cfg(feature = "bytemuck")]
+pub mod mesh;
+mod program_binary;
+pub mod query;
+pub mod readback;
+pub mod shader;
+pub mod shared;
+#[cfg(feature = "state-cache")]
+mod state_cache;
+#[cfg(feature = "bytemuck")]
+pub mod storage;
+pub mod texture;
+pub mod transform_feedback;
+#[cfg(feature = "bytemuck")]
+pub mod uniform;
+pub mod vertex;
+#[cfg(feature = "notify")]
+pub mod watch;
+
+pub use builder::{ContextPriority, EglPlatform, EzglBuilder, SwapBehavior};
+pub use error::{Error, Result};
+pub use handle::{
+    BlendMode, ClipDepth, ClipOrigin, EzglHandle, GlProfile, QualityHint, Setup2d, StereoBuffer,
+};
+
 use gl::{Context, HasContext};
 use glutin::{
-    config::{ConfigSurfaceTypes, ConfigTemplate, ConfigTemplateBuilder, GlConfig},
-    context::{ContextApi, ContextAttributesBuilder, NotCurrentGlContext, PossiblyCurrentContext},
+    config::{ColorBufferType, Config, ConfigSurfaceTypes, ConfigTemplateBuilder, GlConfig},
+    context::{
+        ContextAttributesBuilder, NotCurrentGlContext, PossiblyCurrentContext,
+        PossiblyCurrentGlContext, ReleaseBehavior,
+    },
     display::{Display, GlDisplay},
-    error::Result,
-    surface::{GlSurface, Surface, SurfaceAttributes, SurfaceAttributesBuilder, WindowSurface},
+    error::ErrorKind,
+    surface::{
+        GlSurface, Surface, SurfaceAttributes, SurfaceAttributesBuilder, SwapInterval,
+        WindowSurface,
+    },
 };
 use raw_window_handle::{
     HasRawDisplayHandle, HasRawWindowHandle, RawDisplayHandle, RawWindowHandle,
 };
-use std::{num::NonZeroU32, sync::Arc};
+use std::{
+    cell::{Cell, RefCell},
+    num::NonZeroU32,
+    rc::Rc,
+    time::{Duration, Instant},
+};
 
 /// Duplicate of [glutin::api::glx::XlibErrorHookRegistrar], except without the OS-based feature
 /// gate.
 pub type Reg =
     Box<dyn Fn(Box<dyn Fn(*mut std::ffi::c_void, *mut std::ffi::c_void) -> bool + Send + Sync>)>;
 
-fn default_debug_callback(source: u32, type_: u32, id: u32, severity: u32, message: &str) {
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn default_debug_callback(
+    source: u32,
+    type_: u32,
+    id: u32,
+    severity: u32,
+    message: &str,
+) {
     println!(
         "DEBUG: {}: severity={} source={} type={} id={}",
         message,
-        match severity {
-            gl::DEBUG_SEVERITY_HIGH => "HIGH",
-            gl::DEBUG_SEVERITY_MEDIUM => "MEDIUM",
-            gl::DEBUG_SEVERITY_LOW => "LOW",
-            gl::DEBUG_SEVERITY_NOTIFICATION => "NOTIFICATION",
-            _ => "unknown",
-        },
-        match source {
-            gl::DEBUG_SOURCE_API => "API",
-            gl::DEBUG_SOURCE_WINDOW_SYSTEM => "WINDOW_SYSTEM",
-            gl::DEBUG_SOURCE_SHADER_COMPILER => "SHADER_COMPILER",
-            gl::DEBUG_SOURCE_THIRD_PARTY => "THIRD_PARTY",
-            gl::DEBUG_SOURCE_APPLICATION => "APPLICATION",
-            gl::DEBUG_SOURCE_OTHER => "OTHER",
-            _ => "unknown",
-        },
-        match type_ {
-            gl::DEBUG_TYPE_ERROR => "ERROR",
-            gl::DEBUG_TYPE_DEPRECATED_BEHAVIOR => "DEPRECATED_BEHAVIOR",
-            gl::DEBUG_TYPE_UNDEFINED_BEHAVIOR => "UNDEFINED_BEHAVIOR",
-            gl::DEBUG_TYPE_PORTABILITY => "PORTABILITY",
-            gl::DEBUG_TYPE_PERFORMANCE => "PERFORMANCE",
-            gl::DEBUG_TYPE_MARKER => "MARKER",
-            gl::DEBUG_TYPE_PUSH_GROUP => "PUSH_GROUP",
-            gl::DEBUG_TYPE_POP_GROUP => "POP_GROUP",
-            gl::DEBUG_TYPE_OTHER => "OTHER",
-            _ => "unknown",
-        },
+        debug_severity_name(severity),
+        debug_source_name(source),
+        debug_type_name(type_),
         id,
     );
 }
 
+#[cfg(feature = "tracing")]
+pub(crate) fn default_debug_callback(
+    source: u32,
+    type_: u32,
+    id: u32,
+    severity: u32,
+    message: &str,
+) {
+    macro_rules! event {
+        ($level:expr) => {
+            tracing::event!(
+                $level,
+                severity = debug_severity_name(severity),
+                source = debug_source_name(source),
+                type_ = debug_type_name(type_),
+                id,
+                "{}",
+                message
+            )
+        };
+    }
+
+    match severity {
+        gl::DEBUG_SEVERITY_HIGH => event!(tracing::Level::ERROR),
+        gl::DEBUG_SEVERITY_MEDIUM => event!(tracing::Level::WARN),
+        gl::DEBUG_SEVERITY_LOW => event!(tracing::Level::INFO),
+        _ => event!(tracing::Level::DEBUG),
+    }
+}
+
+fn debug_severity_name(severity: u32) -> &'static str {
+    match severity {
+        gl::DEBUG_SEVERITY_HIGH => "HIGH",
+        gl::DEBUG_SEVERITY_MEDIUM => "MEDIUM",
+        gl::DEBUG_SEVERITY_LOW => "LOW",
+        gl::DEBUG_SEVERITY_NOTIFICATION => "NOTIFICATION",
+        _ => "unknown",
+    }
+}
+
+fn debug_source_name(source: u32) -> &'static str {
+    match source {
+        gl::DEBUG_SOURCE_API => "API",
+        gl::DEBUG_SOURCE_WINDOW_SYSTEM => "WINDOW_SYSTEM",
+        gl::DEBUG_SOURCE_SHADER_COMPILER => "SHADER_COMPILER",
+        gl::DEBUG_SOURCE_THIRD_PARTY => "THIRD_PARTY",
+        gl::DEBUG_SOURCE_APPLICATION => "APPLICATION",
+        gl::DEBUG_SOURCE_OTHER => "OTHER",
+        _ => "unknown",
+    }
+}
+
+fn debug_type_name(type_: u32) -> &'static str {
+    match type_ {
+        gl::DEBUG_TYPE_ERROR => "ERROR",
+        gl::DEBUG_TYPE_DEPRECATED_BEHAVIOR => "DEPRECATED_BEHAVIOR",
+        gl::DEBUG_TYPE_UNDEFINED_BEHAVIOR => "UNDEFINED_BEHAVIOR",
+        gl::DEBUG_TYPE_PORTABILITY => "PORTABILITY",
+        gl::DEBUG_TYPE_PERFORMANCE => "PERFORMANCE",
+        gl::DEBUG_TYPE_MARKER => "MARKER",
+        gl::DEBUG_TYPE_PUSH_GROUP => "PUSH_GROUP",
+        gl::DEBUG_TYPE_POP_GROUP => "POP_GROUP",
+        gl::DEBUG_TYPE_OTHER => "OTHER",
+        _ => "unknown",
+    }
+}
+
 /// Struct handling GL information.
 ///
-/// This type implements Deref into [Context]. Note that [ezgl::gl::HasContext](glow::HasContext)
-/// must be in scope for GL functions to be available.
+/// This type implements Deref into [EzglHandle], which implements Deref into [Context]. Note that
+/// [ezgl::gl::HasContext](glow::HasContext) must be in scope for GL functions to be available.
 pub struct Ezgl {
+    // first, so that the objects it cached are deleted while the context is alive
+    handle: EzglHandle,
     surface: Surface<WindowSurface>,
     glutin: PossiblyCurrentContext,
-    glow: Arc<Context>,
+    display_handle: RawDisplayHandle,
+    window_handle: RawWindowHandle,
+    config: Config,
+    swap_behavior: SwapBehavior,
+    release_behavior: ReleaseBehavior,
+    swap_interval: Cell<Option<SwapInterval>>,
+    adaptive_vsync: Cell<bool>,
+    target_frame_time: Cell<Option<Duration>>,
+    last_frame: Cell<Option<Instant>>,
+    frame_count: Cell<u64>,
+    created: Instant,
+    // last, so that the window outlives the surface
+    #[cfg(feature = "winit")]
+    window: Option<winit::window::Window>,
 }
 
 impl Ezgl {
     /// Set up ezgl with an existing [Window](winit::window::Window).
     ///
     /// Set up ezgl using a winit [Window](winit::window::Window) directly, rather than through
-    /// [HasRawWindowHandle] + [HasRawDisplayHandle] as in [Ezgl::new].
+    /// [HasRawWindowHandle] + [HasRawDisplayHandle] as in [Ezgl::new]. If the window reports a
+    /// zero size, as it can before it's shown on some Wayland compositors, the surface is created
+    /// at 1x1 and should be resized on the first [Resized](winit::event::WindowEvent::Resized)
+    /// event.
+    #[cfg(feature = "winit")]
     pub fn with_winit_window(
         window: &winit::window::Window,
         prefer_samples: Option<u8>,
@@ -86,6 +223,20 @@ impl Ezgl {
         Self::with_winit_window_and_debug_callback(window, prefer_samples, default_debug_callback)
     }
 
+    /// Set up ezgl with a [Window](winit::window::Window), taking ownership of it.
+    ///
+    /// Like [Ezgl::with_winit_window], but the window is kept alongside the context and can be
+    /// accessed with [Ezgl::window], so it doesn't need to be stored separately.
+    #[cfg(feature = "winit")]
+    pub fn with_winit_window_owned(
+        window: winit::window::Window,
+        prefer_samples: Option<u8>,
+    ) -> Result<Self> {
+        let mut ezgl = Self::with_winit_window(&window, prefer_samples)?;
+        ezgl.window = Some(window);
+        Ok(ezgl)
+    }
+
     /// Set up ezgl with an existing [Window](winit::window::Window) directly.
     ///
     /// Set up ezgl using a winit [Window](winit::window::Window) directly, like
@@ -93,20 +244,36 @@ impl Ezgl {
     ///
     /// The [HasContext::enable] function must be called with [gl::DEBUG_OUTPUT]
     /// to enable debug output.
+    #[cfg(feature = "winit")]
     pub fn with_winit_window_and_debug_callback<F: FnMut(u32, u32, u32, u32, &str) + 'static>(
         window: &winit::window::Window,
         prefer_samples: Option<u8>,
         debug_callback: F,
     ) -> Result<Self> {
-        let winit::dpi::PhysicalSize { width, height } = window.inner_size();
-
-        #[cfg(unix)]
-        let reg = Some(Box::new(winit::platform::x11::register_xlib_error_hook) as Reg);
-
-        #[cfg(not(unix))]
-        let reg = None;
+        Self::with_winit_window_and_xlib_error_hook(window, prefer_samples, None, debug_callback)
+    }
 
-        Self::new_with_debug_callback(window, width, height, reg, prefer_samples, debug_callback)
+    /// Set up ezgl with an existing [Window](winit::window::Window) and a custom Xlib error hook
+    /// registrar.
+    ///
+    /// Like [Ezgl::with_winit_window_and_debug_callback], but `reg` is used to register glutin's
+    /// Xlib error hook instead of [winit::platform::x11::register_xlib_error_hook]. This is useful
+    /// if something other than winit also installs an Xlib error handler. If `reg` is None, winit's
+    /// registrar is used.
+    #[cfg(feature = "winit")]
+    pub fn with_winit_window_and_xlib_error_hook<F: FnMut(u32, u32, u32, u32, &str) + 'static>(
+        window: &winit::window::Window,
+        prefer_samples: Option<u8>,
+        reg: Option<Reg>,
+        debug_callback: F,
+    ) -> Result<Self> {
+        EzglBuilder {
+            reg,
+            ..Default::default()
+        }
+        .prefer_samples(prefer_samples)
+        .debug_callback(debug_callback)
+        .build_winit(window)
     }
 
     /// Set up ezgl.
@@ -143,93 +310,398 @@ impl Ezgl {
         prefer_samples: Option<u8>,
         debug_callback: F,
     ) -> Result<Self> {
-        let display_handle = window.raw_display_handle();
+        EzglBuilder {
+            reg,
+            ..Default::default()
+        }
+        .prefer_samples(prefer_samples)
+        .debug_callback(debug_callback)
+        .build(window, width, height)
+    }
+
+    /// Set up ezgl with more options. See [EzglBuilder].
+    pub fn builder() -> EzglBuilder {
+        EzglBuilder::new()
+    }
+
+    /// Find the highest desktop GL version supported for a window.
+    ///
+    /// Creates a temporary context without requesting a specific version, reads the version it
+    /// got, and tears it down again. The window must not currently have an [Ezgl] set up with it.
+    /// On X11 the probe always uses EGL, since no Xlib error hook registrar is available.
+    pub fn probe_max_version<H: HasRawWindowHandle + HasRawDisplayHandle>(
+        window: &H,
+    ) -> Result<(u8, u8)> {
         let window_handle = window.raw_window_handle();
-        let display = create_display(display_handle, window_handle, reg)?;
-        let template = config_template(window_handle);
+        let display = create_display(window.raw_display_handle(), window_handle, None)?;
+        let template = config_template(window_handle).build();
 
         let config = unsafe {
             display
                 .find_configs(template)?
-                .reduce(|accum, config| {
-                    if let Some(samples) = prefer_samples {
-                        if config.num_samples() == samples {
-                            config
-                        } else {
-                            accum
-                        }
-                    } else {
-                        if config.num_samples() > accum.num_samples() {
-                            config
-                        } else {
-                            accum
-                        }
-                    }
-                })
-                .expect("No configs found :(")
+                .next()
+                .ok_or(Error::Unsupported("config"))?
         };
 
-        let attributes = surface_attributes(&window, width, height);
+        let attributes = surface_attributes(window_handle, 1, 1);
         let surface = unsafe { display.create_window_surface(&config, &attributes)? };
         let context_attributes = ContextAttributesBuilder::new().build(Some(window_handle));
+        let context = unsafe { display.create_context(&config, &context_attributes)? };
+        let context = context.make_current(&surface)?;
 
-        let fallback_context_attributes = ContextAttributesBuilder::new()
-            .with_context_api(ContextApi::Gles(None))
-            .build(Some(window_handle));
-
-        let context = unsafe {
-            display
-                .create_context(&config, &context_attributes)
-                .or_else(|_| display.create_context(&config, &fallback_context_attributes))?
-        };
-
-        let glutin = context.make_current(&surface)?;
-        let mut glow = unsafe {
+        let glow = unsafe {
             Context::from_loader_function(|symbol| {
                 let cstring = std::ffi::CString::new(symbol).unwrap();
                 display.get_proc_address(&cstring)
             })
         };
 
-        unsafe {
-            glow.debug_message_callback(debug_callback);
-        }
+        let version = glow.version();
+        let version = (version.major as u8, version.minor as u8);
 
-        let glow = Arc::new(glow);
-
-        Ok(Self {
-            surface,
-            glutin,
-            glow,
-        })
+        drop(glow);
+        context.make_not_current()?;
+        Ok(version)
     }
 
     /// Resize the GL surface.
     ///
     /// This method does not resize the GL viewport. If width or height are zero this method does
-    /// nothing. Delegates to [Surface::resize].
+    /// nothing. Delegates to [Ezgl::resize_nonzero].
     pub fn resize(&self, width: u32, height: u32) {
-        if width == 0 || height == 0 {
-            return;
+        if let (Some(width), Some(height)) = (NonZeroU32::new(width), NonZeroU32::new(height)) {
+            self.resize_nonzero(width, height);
         }
+    }
+
+    /// Resize the GL surface to a size that's known to be nonzero.
+    ///
+    /// This method does not resize the GL viewport. Delegates to [Surface::resize].
+    pub fn resize_nonzero(&self, width: NonZeroU32, height: NonZeroU32) {
+        self.surface.resize(&self.glutin, width, height);
+    }
 
-        self.surface.resize(
-            &self.glutin,
-            NonZeroU32::new(width).unwrap(),
-            NonZeroU32::new(height).unwrap(),
-        );
+    /// Resize the GL surface to a winit window size, e.g. from
+    /// [WindowEvent::Resized](winit::event::WindowEvent::Resized).
+    ///
+    /// Like [Ezgl::resize], this does not resize the GL viewport, and does nothing if either
+    /// dimension is zero.
+    #[cfg(feature = "winit")]
+    pub fn resize_winit(&self, size: winit::dpi::PhysicalSize<u32>) {
+        self.resize(size.width, size.height);
+    }
+
+    /// Get the refresh rate in hertz of the monitor `window` is currently on.
+    ///
+    /// Pass the result to [Ezgl::set_target_fps] to cap at the native refresh rate. Requires the
+    /// winit window, and returns None if the monitor can't be determined or winit doesn't report
+    /// a refresh rate on this platform.
+    #[cfg(feature = "winit")]
+    pub fn refresh_rate(&self, window: &winit::window::Window) -> Option<f32> {
+        let millihertz = window.current_monitor()?.refresh_rate_millihertz()?;
+        Some(millihertz as f32 / 1000.)
     }
 
     /// Display the next frame.
     ///
-    /// Delegates to [Surface::swap_buffers].
+    /// Delegates to [Surface::swap_buffers]. If the swap failed because the surface is no longer
+    /// valid, which can happen transiently on some platforms (e.g. after a resize), returns
+    /// [Error::SurfaceLost]. The surface can then be recreated with [Ezgl::recreate_surface].
     pub fn swap_buffers(&self) -> Result<()> {
-        self.surface.swap_buffers(&self.glutin)
+        self.surface
+            .swap_buffers(&self.glutin)
+            .map_err(|err| match err.error_kind() {
+                ErrorKind::BadSurface
+                | ErrorKind::BadCurrentSurface
+                | ErrorKind::BadNativeWindow => Error::SurfaceLost(err),
+                _ => Error::Glutin(err),
+            })?;
+
+        self.frame_count.set(self.frame_count.get() + 1);
+        Ok(())
+    }
+
+    /// Set the swap interval, e.g. [SwapInterval::Wait] with an interval of 1 for vsync.
+    ///
+    /// Delegates to [Surface::set_swap_interval]. Platforms may reject an interval, in which case
+    /// the error is returned and [Ezgl::swap_interval] keeps the previous value.
+    pub fn set_swap_interval(&self, interval: SwapInterval) -> Result<()> {
+        self.surface.set_swap_interval(&self.glutin, interval)?;
+        self.swap_interval.set(Some(interval));
+        self.adaptive_vsync.set(false);
+        Ok(())
+    }
+
+    /// Get the swap interval last set with [Ezgl::set_swap_interval].
+    ///
+    /// glutin can't query the interval, so this returns None until one has been set successfully,
+    /// since the platform default varies. Also None while adaptive vsync is on, since
+    /// [SwapInterval] can't express it.
+    pub fn swap_interval(&self) -> Option<SwapInterval> {
+        self.swap_interval.get()
+    }
+
+    /// Turn vsync off or on, or make it adaptive.
+    ///
+    /// [VsyncMode::Adaptive] needs `GLX_EXT_swap_control_tear` or `WGL_EXT_swap_control_tear`, and
+    /// falls back to [VsyncMode::On] without them, which is always the case on EGL. Returns the
+    /// mode that was set. Like [Ezgl::set_swap_interval], the mode is kept when the surface is
+    /// recreated.
+    pub fn set_vsync(&self, mode: VsyncMode) -> Result<VsyncMode> {
+        let interval = match mode {
+            VsyncMode::Off => SwapInterval::DontWait,
+            VsyncMode::On => SwapInterval::Wait(NonZeroU32::new(1).unwrap()),
+            VsyncMode::Adaptive => {
+                if self.set_adaptive_swap_interval() {
+                    self.swap_interval.set(None);
+                    self.adaptive_vsync.set(true);
+                    return Ok(VsyncMode::Adaptive);
+                }
+                SwapInterval::Wait(NonZeroU32::new(1).unwrap())
+            }
+        };
+
+        self.set_swap_interval(interval)?;
+        Ok(match mode {
+            VsyncMode::Adaptive => VsyncMode::On,
+            mode => mode,
+        })
+    }
+
+    /// Set a swap interval of -1, which swaps late frames immediately instead of waiting for the
+    /// next vblank. Returns false if the platform doesn't support it.
+    fn set_adaptive_swap_interval(&self) -> bool {
+        #[cfg(all(unix, not(target_os = "macos")))]
+        if let Display::Glx(display) = &self.handle.display {
+            use glutin::{
+                display::{AsRawDisplay, GetDisplayExtensions, RawDisplay},
+                surface::{AsRawSurface, RawSurface},
+            };
+
+            if !display.extensions().contains("GLX_EXT_swap_control_tear") {
+                return false;
+            }
+
+            type SwapIntervalExt = unsafe extern "system" fn(*const std::ffi::c_void, u64, i32);
+
+            if let (RawDisplay::Glx(display), RawSurface::Glx(drawable), Some(swap_interval)) = (
+                display.raw_display(),
+                self.surface.raw_surface(),
+                self.proc_address("glXSwapIntervalEXT"),
+            ) {
+                let swap_interval: SwapIntervalExt = unsafe { std::mem::transmute(swap_interval) };
+                unsafe { swap_interval(display, drawable, -1) };
+                return true;
+            }
+        }
+
+        #[cfg(windows)]
+        if let Display::Wgl(display) = &self.handle.display {
+            use glutin::display::GetDisplayExtensions;
+
+            if !display.extensions().contains("WGL_EXT_swap_control_tear") {
+                return false;
+            }
+
+            type SwapIntervalExt = unsafe extern "system" fn(i32) -> i32;
+
+            if let Some(swap_interval) = self.proc_address("wglSwapIntervalEXT") {
+                let swap_interval: SwapIntervalExt = unsafe { std::mem::transmute(swap_interval) };
+                return unsafe { swap_interval(-1) } != 0;
+            }
+        }
+
+        false
+    }
+
+    /// Set the frame rate that [Ezgl::swap_buffers_capped] limits to, or None to not limit it.
+    ///
+    /// The cap is independent of vsync, e.g. for saving power in an idle UI or for reproducible
+    /// profiling. Non-positive values, and values so small that the frame time overflows a
+    /// [Duration], disable the cap.
+    pub fn set_target_fps(&self, fps: Option<f32>) {
+        let frame_time = fps
+            .filter(|fps| *fps > 0.)
+            .and_then(|fps| Duration::try_from_secs_f32(1. / fps).ok());
+        self.target_frame_time.set(frame_time);
+    }
+
+    /// Display the next frame, first sleeping to stay at or under the target frame rate.
+    ///
+    /// The sleep accounts for time spent since the previous call, so only the remainder of the
+    /// frame is waited out. With no target set by [Ezgl::set_target_fps] this is the same as
+    /// [Ezgl::swap_buffers].
+    pub fn swap_buffers_capped(&self) -> Result<()> {
+        if let (Some(frame_time), Some(last_frame)) =
+            (self.target_frame_time.get(), self.last_frame.get())
+        {
+            let elapsed = last_frame.elapsed();
+            if elapsed < frame_time {
+                std::thread::sleep(frame_time - elapsed);
+            }
+        }
+
+        self.last_frame.set(Some(Instant::now()));
+        self.swap_buffers()
+    }
+
+    /// Get the number of frames displayed so far, counting each successful
+    /// [Ezgl::swap_buffers].
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count.get()
+    }
+
+    /// Get the time since ezgl was set up, e.g. for a `time` uniform in animated shaders.
+    pub fn elapsed(&self) -> Duration {
+        self.created.elapsed()
     }
 
-    /// Increase the reference count of the inner glow [Context].
-    pub fn glow_context(&self) -> Arc<Context> {
-        Arc::clone(&self.glow)
+    /// Recreate the window surface.
+    ///
+    /// Creates a new surface for the window ezgl was set up with and makes the context current
+    /// with it. The window must still be alive. Use after [Ezgl::swap_buffers] returns
+    /// [Error::SurfaceLost]. The swap interval set with [Ezgl::set_swap_interval] or
+    /// [Ezgl::set_vsync] is applied to the new surface.
+    pub fn recreate_surface(&mut self, width: u32, height: u32) -> Result<()> {
+        let attributes = surface_attributes(self.window_handle, width, height);
+        let surface = unsafe {
+            self.handle
+                .display
+                .create_window_surface(&self.config, &attributes)?
+        };
+        self.glutin.make_current(&surface)?;
+        self.surface = surface;
+        if self.adaptive_vsync.get() {
+            self.set_adaptive_swap_interval();
+        } else if let Some(interval) = self.swap_interval.get() {
+            self.surface.set_swap_interval(&self.glutin, interval)?;
+        }
+        self.apply_swap_behavior()
+    }
+
+    /// Make the context current with its surface on this thread, and run `f` with it.
+    ///
+    /// For code that renders into ezgl's context while another context may be current, e.g. a
+    /// plugin in a host with its own context. glutin can't track contexts it didn't create, so the
+    /// previously current context is not restored: ezgl's context is left current afterward, and
+    /// the caller has to make its own context current again. Returns any error from making the
+    /// context current.
+    pub fn with_current<R>(&self, f: impl FnOnce(&Context) -> R) -> Result<R> {
+        if !self.glutin.is_current() {
+            self.glutin.make_current(&self.surface)?;
+        }
+        Ok(f(&self.handle.glow))
+    }
+
+    /// Tear down the context and surface.
+    ///
+    /// Dropping an [Ezgl] releases everything too, but some drivers (e.g. Nvidia on X11) crash on
+    /// exit unless the surface is destroyed before the display. This deletes the objects helpers
+    /// like [EzglHandle::draw_fullscreen_triangle] cached, makes the context not current, then
+    /// drops the surface, the context, and the display in that order, and returns any error from
+    /// making the context not current. Call it at shutdown, after dropping any
+    /// [EzglHandle]s cloned from this one, since those keep the display alive.
+    pub fn destroy(self) -> Result<()> {
+        let Ezgl {
+            surface,
+            glutin,
+            handle,
+            #[cfg(feature = "winit")]
+            window,
+            ..
+        } = self;
+
+        handle.delete_cached_objects();
+        let context = glutin.make_not_current()?;
+        drop(surface);
+        drop(context);
+        drop(handle);
+
+        #[cfg(feature = "winit")]
+        drop(window);
+
+        Ok(())
+    }
+
+    /// Set `EGL_SWAP_BEHAVIOR` on the surface if [SwapBehavior::Preserved] was requested.
+    fn apply_swap_behavior(&self) -> Result<()> {
+        if self.swap_behavior == SwapBehavior::Destroyed {
+            return Ok(());
+        }
+
+        #[cfg(not(target_os = "macos"))]
+        {
+            use glutin::{
+                display::{AsRawDisplay, RawDisplay},
+                surface::{AsRawSurface, RawSurface},
+            };
+
+            if let (RawDisplay::Egl(display), RawSurface::Egl(surface)) = (
+                self.handle.display.raw_display(),
+                self.surface.raw_surface(),
+            ) {
+                const EGL_SWAP_BEHAVIOR: i32 = 0x3093;
+                const EGL_BUFFER_PRESERVED: i32 = 0x3094;
+
+                type SurfaceAttrib = unsafe extern "system" fn(
+                    *const std::ffi::c_void,
+                    *const std::ffi::c_void,
+                    i32,
+                    i32,
+                ) -> u32;
+
+                if let Some(surface_attrib) = self.proc_address("eglSurfaceAttrib") {
+                    let surface_attrib: SurfaceAttrib =
+                        unsafe { std::mem::transmute(surface_attrib) };
+                    let ok = unsafe {
+                        surface_attrib(display, surface, EGL_SWAP_BEHAVIOR, EGL_BUFFER_PRESERVED)
+                    };
+                    if ok != 0 {
+                        return Ok(());
+                    }
+                }
+            }
+        }
+
+        Err(Error::Unsupported("preserved swap behavior"))
+    }
+
+    /// Return GL to a known baseline, e.g. at the start of a frame after third-party code ran.
+    ///
+    /// This resets exactly the following, and nothing else:
+    ///
+    /// - The current program is unbound.
+    /// - The vertex array, `ARRAY_BUFFER`, and `ELEMENT_ARRAY_BUFFER` are unbound, in that order,
+    ///   so the vertex array's element buffer is not changed.
+    /// - The active texture unit is set to `TEXTURE0`, and `TEXTURE_2D` and `TEXTURE_CUBE_MAP` are
+    ///   unbound on it.
+    /// - `BLEND`, `DEPTH_TEST`, `SCISSOR_TEST`, and `CULL_FACE` are disabled.
+    ///
+    /// Framebuffer bindings, the viewport, and blend and depth functions are left alone. With
+    /// `feature = "state-cache"`, the state cache is also invalidated.
+    pub fn reset_state(&self) {
+        unsafe {
+            self.use_program(None);
+            self.bind_vertex_array(None);
+            self.bind_buffer(gl::ARRAY_BUFFER, None);
+            self.bind_buffer(gl::ELEMENT_ARRAY_BUFFER, None);
+            self.active_texture(gl::TEXTURE0);
+            self.bind_texture(gl::TEXTURE_2D, None);
+            self.bind_texture(gl::TEXTURE_CUBE_MAP, None);
+            self.disable(gl::BLEND);
+            self.disable(gl::DEPTH_TEST);
+            self.disable(gl::SCISSOR_TEST);
+            self.disable(gl::CULL_FACE);
+        }
+        self.check_error("reset_state");
+
+        #[cfg(feature = "state-cache")]
+        self.invalidate_state_cache();
+    }
+
+    /// Get a cloneable handle to the context, without access to the surface.
+    pub fn handle(&self) -> EzglHandle {
+        self.handle.clone()
     }
 
     /// Get the (possibly) current glutin context.
@@ -237,20 +709,229 @@ impl Ezgl {
         &self.glutin
     }
 
+    /// Get the configuration the context and surface were created with.
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
     /// Get the surface corresponding with the window.
     pub fn surface(&self) -> &Surface<WindowSurface> {
         &self.surface
     }
+
+    /// Get the number of samples per pixel of the chosen context configuration.
+    ///
+    /// This may differ from the number of samples that was preferred if no configuration with
+    /// exactly that many was available. Zero means the default framebuffer is not multisampled.
+    pub fn sample_count(&self) -> u8 {
+        self.config.num_samples()
+    }
+
+    /// Check whether the surface has a back buffer.
+    ///
+    /// A single-buffered surface is drawn to directly, so [Ezgl::swap_buffers] has nothing to
+    /// swap. glutin knows this from the surface rather than the configuration.
+    pub fn is_double_buffered(&self) -> bool {
+        !self.surface.is_single_buffered()
+    }
+
+    /// Get the exact bit sizes of each channel of the chosen context configuration.
+    ///
+    /// The configuration may have more bits than were requested, e.g. a depth buffer even though
+    /// none was asked for.
+    pub fn config_bits(&self) -> ConfigBits {
+        let (red, green, blue, luminance) = match self.config.color_buffer_type() {
+            Some(ColorBufferType::Rgb {
+                r_size,
+                g_size,
+                b_size,
+            }) => (r_size, g_size, b_size, 0),
+            Some(ColorBufferType::Luminance(size)) => (0, 0, 0, size),
+            None => (0, 0, 0, 0),
+        };
+
+        ConfigBits {
+            red,
+            green,
+            blue,
+            luminance,
+            alpha: self.config.alpha_size(),
+            depth: self.config.depth_size(),
+            stencil: self.config.stencil_size(),
+        }
+    }
+
+    /// Get how many frames old the contents of the back buffer are, for redrawing only what
+    /// changed since then.
+    ///
+    /// An age of 1 means the back buffer holds the previous frame, 2 the frame before that, and so
+    /// on. Returns None if the back buffer is new, or the age is unknown because
+    /// `EGL_EXT_buffer_age` or `GLX_EXT_buffer_age` is unsupported, in which case the whole
+    /// frame must be redrawn. Always None on WGL.
+    pub fn buffer_age(&self) -> Option<u32> {
+        match self.surface.buffer_age() {
+            0 => None,
+            age => Some(age),
+        }
+    }
+
+    /// Check whether the default framebuffer can encode sRGB, and whether encoding is enabled.
+    ///
+    /// Rendering is gamma-correct only when both are true. Note that `FRAMEBUFFER_SRGB` state
+    /// applies to whichever framebuffer is bound, so it is checked as-is.
+    pub fn framebuffer_srgb_state(&self) -> SrgbState {
+        SrgbState {
+            capable: self.config.srgb_capable(),
+            enabled: unsafe { self.is_enabled(gl::FRAMEBUFFER_SRGB) },
+        }
+    }
+
+    /// Get the scheduling priority the driver gave the context, through EGL's
+    /// `IMG_context_priority`.
+    ///
+    /// ezgl doesn't request a priority, since glutin can't pass `EGL_CONTEXT_PRIORITY_LEVEL_IMG`
+    /// when creating a context, so this is normally [ContextPriority::Medium]. Returns None if the
+    /// context doesn't use EGL, the driver doesn't support `IMG_context_priority`, or
+    /// `eglQueryContext` can't be loaded.
+    pub fn context_priority(&self) -> Option<ContextPriority> {
+        #[cfg(not(target_os = "macos"))]
+        use glutin::{
+            context::{AsRawContext, RawContext},
+            display::{AsRawDisplay, RawDisplay},
+        };
+
+        #[cfg(not(target_os = "macos"))]
+        if let (RawDisplay::Egl(display), RawContext::Egl(context)) =
+            (self.handle.display.raw_display(), self.glutin.raw_context())
+        {
+            const EGL_CONTEXT_PRIORITY_LEVEL_IMG: i32 = 0x3100;
+            const EGL_CONTEXT_PRIORITY_HIGH_IMG: i32 = 0x3101;
+            const EGL_CONTEXT_PRIORITY_MEDIUM_IMG: i32 = 0x3102;
+            const EGL_CONTEXT_PRIORITY_LOW_IMG: i32 = 0x3103;
+
+            type QueryContext = unsafe extern "system" fn(
+                *const std::ffi::c_void,
+                *const std::ffi::c_void,
+                i32,
+                *mut i32,
+            ) -> u32;
+
+            let query_context: QueryContext =
+                unsafe { std::mem::transmute(self.proc_address("eglQueryContext")?) };
+
+            let mut value = 0;
+            let ok = unsafe {
+                query_context(display, context, EGL_CONTEXT_PRIORITY_LEVEL_IMG, &mut value)
+            };
+
+            return match value {
+                _ if ok == 0 => None,
+                EGL_CONTEXT_PRIORITY_HIGH_IMG => Some(ContextPriority::High),
+                EGL_CONTEXT_PRIORITY_MEDIUM_IMG => Some(ContextPriority::Medium),
+                EGL_CONTEXT_PRIORITY_LOW_IMG => Some(ContextPriority::Low),
+                _ => None,
+            };
+        }
+
+        None
+    }
+
+    /// Get the platform the EGL display was created on.
+    ///
+    /// Returns None if the context doesn't use EGL, e.g. because GLX was preferred, or the
+    /// platform isn't one of [EglPlatform]'s.
+    pub fn egl_platform(&self) -> Option<EglPlatform> {
+        #[cfg(not(target_os = "macos"))]
+        {
+            use glutin::display::{AsRawDisplay, RawDisplay};
+
+            if let RawDisplay::Egl(_) = self.handle.display.raw_display() {
+                return EglPlatform::from_display_handle(self.display_handle);
+            }
+        }
+
+        None
+    }
+
+    /// Get the window, if ezgl was set up with [Ezgl::with_winit_window_owned].
+    #[cfg(feature = "winit")]
+    pub fn window(&self) -> Option<&winit::window::Window> {
+        self.window.as_ref()
+    }
+
+    /// Get the raw display handle of the window ezgl was set up with.
+    ///
+    /// Useful for calling platform APIs (e.g. display gamma control) without keeping the window
+    /// around.
+    pub fn raw_display_handle(&self) -> RawDisplayHandle {
+        self.display_handle
+    }
+
+    /// Get the raw window handle of the window ezgl was set up with.
+    pub fn raw_window_handle(&self) -> RawWindowHandle {
+        self.window_handle
+    }
+}
+
+/// Whether the default framebuffer encodes to sRGB, from [Ezgl::framebuffer_srgb_state].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SrgbState {
+    /// The surface configuration supports sRGB encoding.
+    pub capable: bool,
+    /// `FRAMEBUFFER_SRGB` is enabled.
+    pub enabled: bool,
+}
+
+impl SrgbState {
+    /// Whether GL converts linear colors to sRGB when writing to the default framebuffer, i.e. the
+    /// configuration is capable and `FRAMEBUFFER_SRGB` is enabled.
+    ///
+    /// When this is false, shaders drawing to the window have to encode sRGB themselves, e.g. by
+    /// passing `output_srgb = true` to imgui-glow-renderer.
+    pub fn encodes(&self) -> bool {
+        self.capable && self.enabled
+    }
+}
+
+/// Vsync mode for [Ezgl::set_vsync].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VsyncMode {
+    /// Swap immediately, which can tear.
+    Off,
+    /// Wait for the vertical blank before swapping.
+    On,
+    /// Wait for the vertical blank, unless the frame is late, in which case swap immediately.
+    /// Late frames tear instead of stuttering.
+    Adaptive,
+}
+
+/// Bits per channel of the context configuration, from [Ezgl::config_bits].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConfigBits {
+    /// Red bits, or zero if the color buffer is not RGB.
+    pub red: u8,
+    /// Green bits, or zero if the color buffer is not RGB.
+    pub green: u8,
+    /// Blue bits, or zero if the color buffer is not RGB.
+    pub blue: u8,
+    /// Luminance bits, or zero if the color buffer is not luminance.
+    pub luminance: u8,
+    /// Alpha bits.
+    pub alpha: u8,
+    /// Depth bits.
+    pub depth: u8,
+    /// Stencil bits.
+    pub stencil: u8,
 }
 
 impl std::ops::Deref for Ezgl {
-    type Target = Context;
+    type Target = EzglHandle;
     fn deref(&self) -> &Self::Target {
-        &self.glow
+        &self.handle
     }
 }
 
-fn create_display(
+pub(crate) fn create_display(
     raw_display: RawDisplayHandle,
     _raw_window_handle: RawWindowHandle,
     _reg: Option<Reg>,
@@ -270,29 +951,113 @@ fn create_display(
     #[cfg(windows)]
     let preference = DisplayApiPreference::Wgl(Some(_raw_window_handle));
 
-    unsafe { Display::new(raw_display, preference) }
+    Ok(unsafe { Display::new(raw_display, preference)? })
 }
 
-fn config_template(raw_window_handle: RawWindowHandle) -> ConfigTemplate {
-    let builder = ConfigTemplateBuilder::new()
+pub(crate) fn config_template(raw_window_handle: RawWindowHandle) -> ConfigTemplateBuilder {
+    ConfigTemplateBuilder::new()
         .with_alpha_size(8)
         .compatible_with_native_window(raw_window_handle)
-        .with_surface_type(ConfigSurfaceTypes::WINDOW);
-
-    builder.build()
+        .with_surface_type(ConfigSurfaceTypes::WINDOW)
 }
 
-fn surface_attributes<H: HasRawWindowHandle + HasRawDisplayHandle>(
-    window: &H,
+/// Surface attributes for a window.
+///
+/// Some platforms report a zero size for a window that hasn't been mapped yet, e.g. on Wayland,
+/// so zero dimensions are replaced with 1. The surface gets its real size on the first resize.
+pub(crate) fn surface_attributes(
+    raw_window_handle: RawWindowHandle,
     width: u32,
     height: u32,
 ) -> SurfaceAttributes<WindowSurface> {
-    let raw_window_handle = window.raw_window_handle();
     SurfaceAttributesBuilder::<WindowSurface>::new()
         .with_srgb(Some(true))
         .build(
             raw_window_handle,
-            NonZeroU32::new(width).unwrap(),
-            NonZeroU32::new(height).unwrap(),
+            NonZeroU32::new(width.max(1)).unwrap(),
+            NonZeroU32::new(height.max(1)).unwrap(),
         )
 }
+
+/// The slot the installed debug callback forwards to.
+pub(crate) struct DebugCallbackSlot {
+    current: RefCell<gl::DebugCallback>,
+    // a replacement made while the callback is running, swapped in once it returns
+    pending: Cell<Option<gl::DebugCallback>>,
+}
+
+impl DebugCallbackSlot {
+    /// Replace the callback, or queue the replacement if the callback is running.
+    pub(crate) fn replace(&self, debug_callback: gl::DebugCallback) {
+        match self.current.try_borrow_mut() {
+            Ok(mut current) => *current = debug_callback,
+            Err(_) => self.pending.set(Some(debug_callback)),
+        }
+    }
+}
+
+/// Install a debug callback that forwards to a swappable slot, for
+/// [EzglHandle::set_debug_callback].
+pub(crate) fn install_debug_callback(
+    glow: &mut Context,
+    debug_callback: gl::DebugCallback,
+) -> Rc<DebugCallbackSlot> {
+    // glow takes the callback by value, so it forwards to a slot that can be swapped later
+    let debug_callback = Rc::new(DebugCallbackSlot {
+        current: RefCell::new(debug_callback),
+        pending: Cell::new(None),
+    });
+    let forward = Rc::clone(&debug_callback);
+    unsafe {
+        glow.debug_message_callback(move |source, type_, id, severity, message| {
+            // a message raised from inside the callback itself is dropped
+            let Ok(mut callback) = forward.current.try_borrow_mut() else {
+                return;
+            };
+            callback(source, type_, id, severity, message);
+            drop(callback);
+
+            if let Some(replacement) = forward.pending.take() {
+                *forward.current.borrow_mut() = replacement;
+            }
+        });
+    }
+
+    debug_callback
+}
+
+/// Convert a GL object name from [HasContext::get_parameter_i32] into an optional framebuffer.
+pub(crate) fn native_framebuffer(name: i32) -> Option<gl::NativeFramebuffer> {
+    NonZeroU32::new(name as u32).map(gl::NativeFramebuffer)
+}
+
+/// Convert a GL object name from [HasContext::get_parameter_i32] into an optional texture.
+pub(crate) fn native_texture(name: i32) -> Option<gl::NativeTexture> {
+    NonZeroU32::new(name as u32).map(gl::NativeTexture)
+}
+
+/// Convert a GL object name from [HasContext::get_parameter_i32] into an optional renderbuffer.
+pub(crate) fn native_renderbuffer(name: i32) -> Option<gl::NativeRenderbuffer> {
+    NonZeroU32::new(name as u32).map(gl::NativeRenderbuffer)
+}
+
+/// Convert a GL object name from [HasContext::get_parameter_i32] into an optional buffer.
+pub(crate) fn native_buffer(name: i32) -> Option<gl::NativeBuffer> {
+    NonZeroU32::new(name as u32).map(gl::NativeBuffer)
+}
+
+/// Convert a GL object name from [HasContext::get_parameter_i32] into an optional program.
+pub(crate) fn native_program(name: i32) -> Option<gl::NativeProgram> {
+    NonZeroU32::new(name as u32).map(gl::NativeProgram)
+}
+
+/// Convert a GL object name from [HasContext::get_parameter_i32] into an optional transform
+/// feedback object.
+pub(crate) fn native_transform_feedback(name: i32) -> Option<gl::NativeTransformFeedback> {
+    NonZeroU32::new(name as u32).map(gl::NativeTransformFeedback)
+}
+
+/// Convert a GL object name from [HasContext::get_parameter_i32] into an optional vertex array.
+pub(crate) fn native_vertex_array(name: i32) -> Option<gl::NativeVertexArray> {
+    NonZeroU32::new(name as u32).map(gl::NativeVertexArray)
+}
@@ -0,0 +1,156 @@
+//! Indexed meshes, enabled with `feature = "bytemuck"`.
+
+use crate::{
+    gl::{self, Context, HasContext, NativeBuffer, NativeVertexArray},
+    native_buffer, native_vertex_array,
+    vertex::VertexLayout,
+    EzglHandle, Result,
+};
+use bytemuck::Pod;
+use std::sync::Arc;
+
+/// A vertex array with its own vertex and index buffers, for drawing indexed triangles.
+///
+/// ```no_run
+/// # let ezgl: ezgl::Ezgl = todo!();
+/// use ezgl::{gl, mesh::Mesh, vertex::VertexLayout};
+///
+/// let vertices: [[f32; 2]; 4] = [[-0.5, -0.5], [0.5, -0.5], [0.5, 0.5], [-0.5, 0.5]];
+/// let layout = VertexLayout::new().attrib(0, 2, gl::FLOAT, false);
+/// let quad = Mesh::new(&ezgl, &vertices, &[0, 1, 2, 0, 2, 3], &layout).unwrap();
+///
+/// quad.draw(&ezgl);
+/// ```
+///
+/// The vertex array and buffers are deleted when this is dropped.
+pub struct Mesh {
+    glow: Arc<Context>,
+    vertex_array: NativeVertexArray,
+    vertex_buffer: NativeBuffer,
+    index_buffer: NativeBuffer,
+    index_count: i32,
+}
+
+impl Mesh {
+    /// Create a mesh from interleaved vertices and triangle indices.
+    ///
+    /// `layout` describes the attributes of `V`, and its stride must match the size of `V`. The
+    /// previous vertex array and `ARRAY_BUFFER` bindings are restored afterward.
+    pub fn new<V: Pod>(
+        ezgl: &EzglHandle,
+        vertices: &[V],
+        indices: &[u32],
+        layout: &VertexLayout,
+    ) -> Result<Self> {
+        debug_assert_eq!(
+            layout.stride() as usize,
+            std::mem::size_of::<V>(),
+            "Vertex layout stride doesn't match the vertex type"
+        );
+
+        // create the objects one at a time, so the earlier ones can be deleted if a later one
+        // can't be created
+        let vertex_array = unsafe { ezgl.create_vertex_array()? };
+        let vertex_buffer = match ezgl.create_buffer_object() {
+            Ok(vertex_buffer) => vertex_buffer,
+            Err(err) => {
+                unsafe { ezgl.delete_vertex_array(vertex_array) };
+                return Err(err);
+            }
+        };
+        let index_buffer = match ezgl.create_buffer_object() {
+            Ok(index_buffer) => index_buffer,
+            Err(err) => {
+                unsafe {
+                    ezgl.delete_vertex_array(vertex_array);
+                    ezgl.delete_buffer(vertex_buffer);
+                }
+                return Err(err);
+            }
+        };
+
+        let mesh = Self {
+            glow: ezgl.glow_context(),
+            vertex_array,
+            vertex_buffer,
+            index_buffer,
+            index_count: indices.len() as i32,
+        };
+
+        ezgl.upload_buffer(
+            gl::ARRAY_BUFFER,
+            mesh.vertex_buffer,
+            bytemuck::cast_slice(vertices),
+            gl::STATIC_DRAW,
+        );
+        // a buffer can be uploaded through any target, and ELEMENT_ARRAY_BUFFER would change the
+        // element array of whatever vertex array is bound
+        ezgl.upload_buffer(
+            gl::ARRAY_BUFFER,
+            mesh.index_buffer,
+            bytemuck::cast_slice(indices),
+            gl::STATIC_DRAW,
+        );
+
+        unsafe {
+            let prev_vertex_array = ezgl.get_parameter_i32(gl::VERTEX_ARRAY_BINDING);
+            let prev_buffer = ezgl.get_parameter_i32(gl::ARRAY_BUFFER_BINDING);
+
+            // the attribute pointers capture the ARRAY_BUFFER binding, and the element array
+            // binding is part of the vertex array state
+            ezgl.bind_vertex_array(Some(mesh.vertex_array));
+            ezgl.bind_buffer(gl::ARRAY_BUFFER, Some(mesh.vertex_buffer));
+            ezgl.bind_buffer(gl::ELEMENT_ARRAY_BUFFER, Some(mesh.index_buffer));
+            layout.apply(ezgl);
+
+            ezgl.bind_vertex_array(native_vertex_array(prev_vertex_array));
+            ezgl.bind_buffer(gl::ARRAY_BUFFER, native_buffer(prev_buffer));
+            ezgl.check_error("Mesh::new");
+
+            Ok(mesh)
+        }
+    }
+
+    /// Draw the mesh as triangles with the current program.
+    ///
+    /// The previous vertex array binding is restored afterward.
+    pub fn draw(&self, ezgl: &EzglHandle) {
+        unsafe {
+            let prev = ezgl.get_parameter_i32(gl::VERTEX_ARRAY_BINDING);
+            ezgl.bind_vertex_array(Some(self.vertex_array));
+            ezgl.draw_elements(gl::TRIANGLES, self.index_count, gl::UNSIGNED_INT, 0);
+            ezgl.bind_vertex_array(native_vertex_array(prev));
+        }
+        ezgl.check_error("Mesh::draw");
+    }
+
+    /// Get the number of indices.
+    pub fn index_count(&self) -> u32 {
+        self.index_count as u32
+    }
+
+    /// Get the raw GL vertex array.
+    pub fn vertex_array(&self) -> NativeVertexArray {
+        self.vertex_array
+    }
+
+    /// Get the raw GL vertex buffer.
+    pub fn vertex_buffer(&self) -> NativeBuffer {
+        self.vertex_buffer
+    }
+
+    /// Get the raw GL index buffer.
+    pub fn index_buffer(&self) -> NativeBuffer {
+        self.index_buffer
+    }
+}
+
+impl Drop for Mesh {
+    fn drop(&mut self) {
+        unsafe {
+            self.glow.delete_vertex_array(self.vertex_array);
+            self.glow.delete_buffer(self.vertex_buffer);
+            self.glow.delete_buffer(self.index_buffer);
+        }
+    }
+}
@@ -0,0 +1,105 @@
+//! Program binary retrieval, for caching linked shaders.
+
+use crate::{
+    gl::{self, HasContext, NativeProgram},
+    Error, EzglHandle, Result,
+};
+use std::ffi::c_void;
+
+type GetProgramiv = unsafe extern "system" fn(u32, u32, *mut i32);
+type GetProgramBinary = unsafe extern "system" fn(u32, i32, *mut i32, *mut u32, *mut c_void);
+type ProgramBinary = unsafe extern "system" fn(u32, u32, *const c_void, i32);
+
+impl EzglHandle {
+    fn supports_program_binary(&self) -> bool {
+        let version = self.version();
+        self.has_extension("GL_ARB_get_program_binary")
+            || self.has_extension("GL_OES_get_program_binary")
+            || if version.is_embedded {
+                version.major >= 3
+            } else {
+                (version.major, version.minor) >= (4, 1)
+            }
+    }
+
+    /// Get the binary of a linked program, along with its driver-specific format.
+    ///
+    /// Returns None if `ARB_get_program_binary` is unsupported or the driver has no binary for the
+    /// program. Binaries only load on the same driver they came from, so a cache should be keyed
+    /// on the `VENDOR`, `RENDERER`, and `VERSION` strings and discarded when they change. Some
+    /// drivers only keep a binary if `PROGRAM_BINARY_RETRIEVABLE_HINT` was set before linking.
+    pub fn get_program_binary(&self, program: NativeProgram) -> Option<(u32, Vec<u8>)> {
+        if !self.supports_program_binary() {
+            return None;
+        }
+
+        let get_programiv: GetProgramiv =
+            unsafe { std::mem::transmute(self.proc_address("glGetProgramiv")?) };
+        let get_program_binary: GetProgramBinary =
+            unsafe { std::mem::transmute(self.proc_address("glGetProgramBinary")?) };
+
+        let mut length = 0;
+        unsafe { get_programiv(program.0.get(), gl::PROGRAM_BINARY_LENGTH, &mut length) };
+        if length <= 0 {
+            return None;
+        }
+
+        let mut data = vec![0u8; length as usize];
+        let mut format = 0;
+        let mut written = 0;
+        unsafe {
+            get_program_binary(
+                program.0.get(),
+                length,
+                &mut written,
+                &mut format,
+                data.as_mut_ptr() as *mut c_void,
+            )
+        };
+        self.check_error("get_program_binary");
+
+        if written <= 0 {
+            return None;
+        }
+        data.truncate(written as usize);
+
+        Some((format, data))
+    }
+
+    /// Load a program from a binary returned by [EzglHandle::get_program_binary].
+    ///
+    /// Returns [Error::Unsupported] if `ARB_get_program_binary` is unsupported, or [Error::Gl] with
+    /// the info log if the driver rejected the binary. A rejected binary is expected after a
+    /// driver update, so fall back to compiling from source.
+    pub fn program_binary(&self, program: NativeProgram, format: u32, data: &[u8]) -> Result<()> {
+        if !self.supports_program_binary() {
+            return Err(Error::Unsupported("ARB_get_program_binary"));
+        }
+
+        let program_binary: ProgramBinary = unsafe {
+            std::mem::transmute(
+                self.proc_address("glProgramBinary")
+                    .ok_or(Error::Unsupported("ARB_get_program_binary"))?,
+            )
+        };
+
+        unsafe {
+            program_binary(
+                program.0.get(),
+                format,
+                data.as_ptr() as *const c_void,
+                data.len() as i32,
+            );
+
+            if self.get_program_link_status(program) {
+                self.check_error("program_binary");
+                Ok(())
+            } else {
+                // a format the driver no longer knows raises INVALID_ENUM as well, which is part
+                // of the rejection rather than a bug
+                let _ = self.get_error();
+                Err(Error::Gl(self.get_program_info_log(program)))
+            }
+        }
+    }
+}
@@ -0,0 +1,205 @@
+//! Asynchronous GL query objects.
+
+use crate::{
+    gl::{self, Context, HasContext, NativeQuery},
+    Ezgl, EzglHandle, Result,
+};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// A GL query object.
+///
+/// Query results are produced by the GPU some time after the commands they measure have been
+/// submitted, so reading them back is non-blocking: [Query::samples_passed] returns None until
+/// the result is available. Typically a query is read one or more frames after it was issued.
+///
+/// The query object is deleted when this is dropped.
+pub struct Query {
+    glow: Arc<Context>,
+    query: NativeQuery,
+    target: u32,
+}
+
+impl Query {
+    /// Create an occlusion query.
+    ///
+    /// On desktop GL this counts the number of samples that pass the depth and stencil tests. GLES
+    /// only supports boolean occlusion queries, so there the count is either 0 or 1.
+    pub fn occlusion(ezgl: &EzglHandle) -> Result<Self> {
+        let target = if ezgl.version().is_embedded {
+            gl::ANY_SAMPLES_PASSED
+        } else {
+            gl::SAMPLES_PASSED
+        };
+
+        Self::new(ezgl, target)
+    }
+
+    /// Create a timer query.
+    ///
+    /// Measures the GPU time taken by the commands inside [Query::measure]. Requires desktop GL 3.3
+    /// or `EXT_disjoint_timer_query` on GLES.
+    pub fn timer(ezgl: &EzglHandle) -> Result<Self> {
+        Self::new(ezgl, gl::TIME_ELAPSED)
+    }
+
+    fn new(ezgl: &EzglHandle, target: u32) -> Result<Self> {
+        let query = unsafe { ezgl.create_query()? };
+        ezgl.check_error("Query::new");
+        Ok(Self {
+            glow: ezgl.glow_context(),
+            query,
+            target,
+        })
+    }
+
+    /// Run GL commands inside the query.
+    ///
+    /// Only one query of each type may be active at a time, so `f` must not itself begin a query
+    /// of the same type.
+    pub fn measure(&self, ezgl: &EzglHandle, f: impl FnOnce()) {
+        unsafe { ezgl.begin_query(self.target, self.query) };
+        f();
+        unsafe { ezgl.end_query(self.target) };
+        ezgl.check_error("Query::measure");
+    }
+
+    /// Get the number of samples that passed, if the result is available.
+    ///
+    /// This does not wait for the GPU. If the result is not yet ready, returns None.
+    pub fn samples_passed(&self, ezgl: &EzglHandle) -> Option<u64> {
+        let samples = unsafe {
+            if ezgl.get_query_parameter_u32(self.query, gl::QUERY_RESULT_AVAILABLE) == 0 {
+                None
+            } else {
+                Some(ezgl.get_query_parameter_u32(self.query, gl::QUERY_RESULT) as u64)
+            }
+        };
+        ezgl.check_error("Query::samples_passed");
+        samples
+    }
+
+    /// Get the elapsed GPU time of a timer query, if the result is available.
+    ///
+    /// This does not wait for the GPU. If the result is not yet ready, returns None. On GLES, also
+    /// returns None if the driver reports a disjoint event like a GPU frequency change, since the
+    /// result can't be trusted then.
+    pub fn time_elapsed(&self, ezgl: &EzglHandle) -> Option<Duration> {
+        const GPU_DISJOINT_EXT: u32 = 0x8FBB;
+
+        let version = ezgl.version();
+        unsafe {
+            if ezgl.get_query_parameter_u32(self.query, gl::QUERY_RESULT_AVAILABLE) == 0 {
+                return None;
+            }
+
+            if version.is_embedded && ezgl.get_parameter_i32(GPU_DISJOINT_EXT) != 0 {
+                return None;
+            }
+        }
+
+        // glow doesn't wrap glGetQueryObjectui64v
+        type GetQueryObjectU64 = unsafe extern "system" fn(u32, u32, *mut u64);
+        let name = if version.is_embedded {
+            "glGetQueryObjectui64vEXT"
+        } else {
+            "glGetQueryObjectui64v"
+        };
+
+        let nanos = match ezgl.proc_address(name) {
+            Some(get_query_object_u64) => unsafe {
+                let get_query_object_u64: GetQueryObjectU64 =
+                    std::mem::transmute(get_query_object_u64);
+                let mut nanos = 0;
+                get_query_object_u64(self.query.0.get(), gl::QUERY_RESULT, &mut nanos);
+                nanos
+            },
+            // the 32-bit result only wraps after about four seconds
+            None => unsafe { ezgl.get_query_parameter_u32(self.query, gl::QUERY_RESULT) as u64 },
+        };
+        ezgl.check_error("Query::time_elapsed");
+
+        Some(Duration::from_nanos(nanos))
+    }
+
+    /// Get the raw GL query object.
+    pub fn raw(&self) -> NativeQuery {
+        self.query
+    }
+}
+
+impl Drop for Query {
+    fn drop(&mut self) {
+        unsafe { self.glow.delete_query(self.query) };
+    }
+}
+
+/// Timing information for a frame, returned from [FrameTimer::end_and_swap].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameTime {
+    /// GPU time of the previous frame, or None if it isn't available yet.
+    pub gpu: Option<Duration>,
+    /// CPU time between [FrameTimer::begin] and [FrameTimer::end_and_swap], not including the
+    /// buffer swap.
+    pub cpu: Duration,
+}
+
+/// Per-frame GPU and CPU timer.
+///
+/// Call [FrameTimer::begin] at the start of each frame and [FrameTimer::end_and_swap] in place of
+/// [Ezgl::swap_buffers]. Two timer queries are alternated between frames so that reading the GPU
+/// time never stalls waiting for the frame in flight, which means the reported GPU time lags one
+/// frame behind.
+pub struct FrameTimer {
+    queries: [Query; 2],
+    pending: [bool; 2],
+    current: usize,
+    frame_start: Instant,
+}
+
+impl FrameTimer {
+    /// Create a frame timer. See [Query::timer] for requirements.
+    pub fn new(ezgl: &Ezgl) -> Result<Self> {
+        Ok(Self {
+            queries: [Query::timer(ezgl)?, Query::timer(ezgl)?],
+            pending: [false; 2],
+            current: 0,
+            frame_start: Instant::now(),
+        })
+    }
+
+    /// Start timing a frame.
+    pub fn begin(&mut self, ezgl: &Ezgl) {
+        self.frame_start = Instant::now();
+        let query = &self.queries[self.current];
+        unsafe { ezgl.begin_query(query.target, query.query) };
+        ezgl.check_error("FrameTimer::begin");
+    }
+
+    /// Stop timing the frame and display it.
+    ///
+    /// Delegates to [Ezgl::swap_buffers].
+    pub fn end_and_swap(&mut self, ezgl: &Ezgl) -> Result<FrameTime> {
+        let query = &self.queries[self.current];
+        unsafe { ezgl.end_query(query.target) };
+        ezgl.check_error("FrameTimer::end_and_swap");
+        self.pending[self.current] = true;
+
+        let cpu = self.frame_start.elapsed();
+        ezgl.swap_buffers()?;
+
+        let previous = 1 - self.current;
+        let gpu = if self.pending[previous] {
+            let time = self.queries[previous].time_elapsed(ezgl);
+            self.pending[previous] = time.is_none();
+            time
+        } else {
+            None
+        };
+
+        self.current = previous;
+        Ok(FrameTime { gpu, cpu })
+    }
+}
@@ -0,0 +1,135 @@
+//! Asynchronous pixel readback.
+
+use crate::{
+    fence::{Fence, FenceStatus},
+    gl::{self, Context, HasContext, NativeBuffer, PixelPackData},
+    native_buffer, EzglHandle, Result,
+};
+use std::{sync::Arc, time::Duration};
+
+/// Reads pixels back from the GPU without stalling, using pixel buffer objects.
+///
+/// [PboReadback::read] queues a copy of a region of the current read framebuffer into one of two
+/// pixel buffers, and [PboReadback::try_take] returns the oldest queued copy once the GPU has
+/// finished it. Pixels are `RGBA8`, with rows ordered bottom to top as with
+/// [read_pixels](HasContext::read_pixels).
+///
+/// At most two reads can be in flight. Queueing a third before taking the first discards the
+/// first. The pixel pack buffer binding is restored after each call, and the buffers are deleted
+/// when this is dropped.
+pub struct PboReadback {
+    glow: Arc<Context>,
+    buffers: [NativeBuffer; 2],
+    fences: [Option<Fence>; 2],
+    next: usize,
+    width: i32,
+    height: i32,
+}
+
+impl PboReadback {
+    /// Create a readback ring for regions of `width` by `height` pixels.
+    pub fn new(ezgl: &EzglHandle, width: u32, height: u32) -> Result<Self> {
+        let first = ezgl.create_buffer_object()?;
+        let second = match ezgl.create_buffer_object() {
+            Ok(second) => second,
+            Err(err) => {
+                unsafe { ezgl.delete_buffer(first) };
+                return Err(err);
+            }
+        };
+
+        let readback = Self {
+            glow: ezgl.glow_context(),
+            buffers: [first, second],
+            fences: [None, None],
+            next: 0,
+            width: width as i32,
+            height: height as i32,
+        };
+
+        for buffer in readback.buffers {
+            ezgl.allocate_buffer(
+                gl::PIXEL_PACK_BUFFER,
+                buffer,
+                readback.size(),
+                gl::STREAM_READ,
+            );
+        }
+        ezgl.check_error("PboReadback::new");
+
+        Ok(readback)
+    }
+
+    fn size(&self) -> i32 {
+        self.width * self.height * 4
+    }
+
+    /// Queue a read of the region starting at `x`, `y` of the current read framebuffer.
+    pub fn read(&mut self, ezgl: &EzglHandle, x: i32, y: i32) -> Result<()> {
+        unsafe {
+            let prev = ezgl.get_parameter_i32(gl::PIXEL_PACK_BUFFER_BINDING);
+            ezgl.bind_buffer(gl::PIXEL_PACK_BUFFER, Some(self.buffers[self.next]));
+            ezgl.read_pixels(
+                x,
+                y,
+                self.width,
+                self.height,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                PixelPackData::BufferOffset(0),
+            );
+            ezgl.bind_buffer(gl::PIXEL_PACK_BUFFER, native_buffer(prev));
+        }
+        ezgl.check_error("PboReadback::read");
+
+        self.fences[self.next] = Some(Fence::insert(ezgl)?);
+        self.next = 1 - self.next;
+        Ok(())
+    }
+
+    /// Get the pixels of the oldest queued read, if the GPU has finished it.
+    ///
+    /// This does not wait for the GPU. Returns None if no read is queued or the oldest one is not
+    /// finished yet.
+    pub fn try_take(&mut self, ezgl: &EzglHandle) -> Option<Vec<u8>> {
+        // self.next is the slot that will be written next, which is the older of the two
+        let oldest = if self.fences[self.next].is_some() {
+            self.next
+        } else {
+            1 - self.next
+        };
+
+        match self.fences[oldest].as_ref()?.wait(ezgl, Duration::ZERO) {
+            FenceStatus::AlreadySignaled | FenceStatus::Satisfied => {}
+            FenceStatus::TimeoutExpired | FenceStatus::Failed => return None,
+        }
+
+        self.fences[oldest] = None;
+
+        let mut pixels = vec![0; self.size() as usize];
+        let mapped =
+            ezgl.copy_from_buffer(gl::PIXEL_PACK_BUFFER, self.buffers[oldest], &mut pixels);
+        ezgl.check_error("PboReadback::try_take");
+        mapped.then_some(pixels)
+    }
+
+    /// Get the width of the region read.
+    pub fn width(&self) -> u32 {
+        self.width as u32
+    }
+
+    /// Get the height of the region read.
+    pub fn height(&self) -> u32 {
+        self.height as u32
+    }
+}
+
+impl Drop for PboReadback {
+    fn drop(&mut self) {
+        unsafe {
+            for buffer in self.buffers {
+                self.glow.delete_buffer(buffer);
+            }
+        }
+    }
+}
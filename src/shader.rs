@@ -0,0 +1,404 @@
+//! Shader compilation with error reporting, and program introspection.
+
+use crate::{
+    gl::{self, Context, HasContext, NativeProgram, NativeShader, NativeUniformLocation},
+    EzglHandle,
+};
+use std::{cell::Cell, rc::Rc, sync::Arc};
+
+/// An error from compiling or linking a shader program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProgramError {
+    /// GL failed to create a shader or program object.
+    Create(String),
+
+    /// A shader failed to compile. Contains the stage, e.g. [gl::VERTEX_SHADER], and the info log.
+    Compile { stage: u32, log: String },
+
+    /// The program failed to link. Contains the info log.
+    Link(String),
+}
+
+impl std::fmt::Display for ProgramError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProgramError::Create(err) => write!(f, "Could not create GL object: {}", err),
+            ProgramError::Compile { stage, log } => {
+                write!(
+                    f,
+                    "{} shader failed to compile: {}",
+                    stage_name(*stage),
+                    log
+                )
+            }
+            ProgramError::Link(log) => write!(f, "Program failed to link: {}", log),
+        }
+    }
+}
+
+impl std::error::Error for ProgramError {}
+
+/// The active uniforms and attributes of a linked program, and whether it validates, from
+/// [EzglHandle::program_introspection].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProgramInfo {
+    /// Uniforms the program uses. Uniforms the compiler optimized out are not active.
+    pub uniforms: Vec<UniformInfo>,
+    /// Vertex attributes the program uses.
+    pub attributes: Vec<AttributeInfo>,
+    /// The validation status, or None if `glValidateProgram` can't be loaded.
+    pub valid: Option<bool>,
+    /// The info log after validation, which explains why the program wouldn't run.
+    pub validate_log: String,
+}
+
+/// An active uniform, from [ProgramInfo::uniforms].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UniformInfo {
+    /// The name, with `[0]` appended for arrays.
+    pub name: String,
+    /// The location, or None for uniforms in a uniform block.
+    pub location: Option<NativeUniformLocation>,
+    /// The type, e.g. [gl::FLOAT_VEC4].
+    pub ty: u32,
+    /// The number of array elements, or 1 if it's not an array.
+    pub size: i32,
+}
+
+/// An active vertex attribute, from [ProgramInfo::attributes].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttributeInfo {
+    /// The name.
+    pub name: String,
+    /// The location, or None for built-in attributes like `gl_VertexID`.
+    pub location: Option<u32>,
+    /// The type, e.g. [gl::FLOAT_VEC3].
+    pub ty: u32,
+    /// The number of array elements, or 1 if it's not an array.
+    pub size: i32,
+}
+
+fn stage_name(stage: u32) -> &'static str {
+    match stage {
+        gl::VERTEX_SHADER => "Vertex",
+        gl::FRAGMENT_SHADER => "Fragment",
+        gl::GEOMETRY_SHADER => "Geometry",
+        gl::TESS_CONTROL_SHADER => "Tessellation control",
+        gl::TESS_EVALUATION_SHADER => "Tessellation evaluation",
+        gl::COMPUTE_SHADER => "Compute",
+        _ => "Unknown",
+    }
+}
+
+/// Insert `#define <name>` into GLSL source, after the `#version` line if there is one.
+fn with_define(source: &str, name: &str) -> String {
+    let trimmed = source.trim_start();
+    if !trimmed.starts_with("#version") {
+        return format!("#define {}\n{}", name, source);
+    }
+
+    let start = source.len() - trimmed.len();
+    match source[start..].find('\n') {
+        Some(end) => {
+            let (version, rest) = source.split_at(start + end + 1);
+            format!("{}#define {}\n{}", version, name, rest)
+        }
+        None => format!("{}\n#define {}\n", source, name),
+    }
+}
+
+impl EzglHandle {
+    /// Get the `#version` line for shaders targeting the context, to prepend to sources that don't
+    /// have one.
+    ///
+    /// The context may be GLES if desktop GL was unavailable, so this returns
+    /// `"#version 300 es\nprecision mediump float;\n"` on GLES and `"#version 330 core\n"`
+    /// otherwise. Shaders written against GLSL 3.30 mostly compile unchanged as GLSL ES 3.00.
+    ///
+    /// ```no_run
+    /// # let ezgl: ezgl::Ezgl = todo!();
+    /// let fragment = format!(
+    ///     "{}out vec4 color; void main() {{ color = vec4(1.0); }}",
+    ///     ezgl.glsl_version_header(),
+    /// );
+    /// ```
+    pub fn glsl_version_header(&self) -> &'static str {
+        if self.version().is_embedded {
+            "#version 300 es\nprecision mediump float;\n"
+        } else {
+            "#version 330 core\n"
+        }
+    }
+
+    /// Compile a shader, returning the info log if compilation fails.
+    ///
+    /// `stage` is the shader type, e.g. [gl::VERTEX_SHADER]. The shader is deleted if it fails to
+    /// compile.
+    pub fn compile_shader_checked(
+        &self,
+        stage: u32,
+        source: &str,
+    ) -> Result<NativeShader, ProgramError> {
+        unsafe {
+            let shader = self.create_shader(stage).map_err(ProgramError::Create)?;
+            self.shader_source(shader, source);
+            self.compile_shader(shader);
+            self.check_error("compile_shader_checked");
+
+            if self.get_shader_compile_status(shader) {
+                Ok(shader)
+            } else {
+                let log = self.get_shader_info_log(shader);
+                self.delete_shader(shader);
+                Err(ProgramError::Compile { stage, log })
+            }
+        }
+    }
+
+    /// Link compiled shaders into a program, returning the info log if linking fails.
+    ///
+    /// The shaders are detached afterward but not deleted. The program is deleted if it fails to
+    /// link.
+    pub fn link_program_checked(
+        &self,
+        shaders: &[NativeShader],
+    ) -> Result<NativeProgram, ProgramError> {
+        self.link_program_with_varyings(shaders, &[], gl::INTERLEAVED_ATTRIBS)
+    }
+
+    /// Link compiled shaders into a program that captures `varyings` with transform feedback.
+    ///
+    /// The varyings have to be set before linking, so they can't be added to a linked program.
+    /// `buffer_mode` is [gl::INTERLEAVED_ATTRIBS] to write them all to one buffer, or
+    /// [gl::SEPARATE_ATTRIBS] to write each to its own. Capture with
+    /// [TransformFeedback](crate::transform_feedback::TransformFeedback). Otherwise the same as
+    /// [EzglHandle::link_program_checked].
+    pub fn link_program_with_varyings(
+        &self,
+        shaders: &[NativeShader],
+        varyings: &[&str],
+        buffer_mode: u32,
+    ) -> Result<NativeProgram, ProgramError> {
+        unsafe {
+            let program = self.create_program().map_err(ProgramError::Create)?;
+            for shader in shaders {
+                self.attach_shader(program, *shader);
+            }
+            if !varyings.is_empty() {
+                self.transform_feedback_varyings(program, varyings, buffer_mode);
+            }
+            self.link_program(program);
+            for shader in shaders {
+                self.detach_shader(program, *shader);
+            }
+            self.check_error("link_program_with_varyings");
+
+            if self.get_program_link_status(program) {
+                Ok(program)
+            } else {
+                let log = self.get_program_info_log(program);
+                self.delete_program(program);
+                Err(ProgramError::Link(log))
+            }
+        }
+    }
+
+    /// Build a program from a single source with both vertex and fragment stages.
+    ///
+    /// The source is compiled once with `VERTEX` defined and once with `FRAGMENT` defined, so the
+    /// stages go in `#ifdef VERTEX` and `#ifdef FRAGMENT` sections. The defines are inserted after
+    /// the `#version` line, which must be the first non-blank line if present.
+    ///
+    /// ```glsl
+    /// #version 330 core
+    /// #ifdef VERTEX
+    /// layout(location = 0) in vec2 position;
+    /// void main() { gl_Position = vec4(position, 0.0, 1.0); }
+    /// #endif
+    /// #ifdef FRAGMENT
+    /// out vec4 color;
+    /// void main() { color = vec4(1.0); }
+    /// #endif
+    /// ```
+    pub fn program_from_combined_source(
+        &self,
+        source: &str,
+    ) -> Result<NativeProgram, ProgramError> {
+        self.program_from_sources(
+            &with_define(source, "VERTEX"),
+            &with_define(source, "FRAGMENT"),
+        )
+    }
+
+    /// Build a compute program from a single compute shader source.
+    ///
+    /// The shader is deleted once the program is linked. Dispatch it with
+    /// [EzglHandle::dispatch_compute].
+    pub fn program_from_compute_source(&self, source: &str) -> Result<NativeProgram, ProgramError> {
+        let compute = self.compile_shader_checked(gl::COMPUTE_SHADER, source)?;
+        let program = self.link_program_checked(&[compute]);
+        unsafe { self.delete_shader(compute) };
+
+        program
+    }
+
+    /// Build a program from vertex and fragment shader sources.
+    ///
+    /// The shaders are deleted once the program is linked.
+    pub fn program_from_sources(
+        &self,
+        vertex: &str,
+        fragment: &str,
+    ) -> Result<NativeProgram, ProgramError> {
+        let vertex = self.compile_shader_checked(gl::VERTEX_SHADER, vertex)?;
+        let fragment = match self.compile_shader_checked(gl::FRAGMENT_SHADER, fragment) {
+            Ok(fragment) => fragment,
+            Err(err) => {
+                unsafe { self.delete_shader(vertex) };
+                return Err(err);
+            }
+        };
+
+        let program = self.link_program_checked(&[vertex, fragment]);
+        unsafe {
+            self.delete_shader(vertex);
+            self.delete_shader(fragment);
+        }
+
+        program
+    }
+
+    /// List the active uniforms and attributes of a linked program, and validate it.
+    ///
+    /// Useful for debugging a uniform that doesn't seem to update: it may have been optimized
+    /// out, or its name may not match. Validation checks the program against the current GL
+    /// state, e.g. that samplers of different types aren't bound to the same texture unit, so
+    /// call this right before the draw call in question.
+    pub fn program_introspection(&self, program: NativeProgram) -> ProgramInfo {
+        let uniforms = unsafe {
+            (0..self.get_active_uniforms(program))
+                .filter_map(|index| self.get_active_uniform(program, index))
+                .map(|uniform| UniformInfo {
+                    location: self.get_uniform_location(program, &uniform.name),
+                    name: uniform.name,
+                    ty: uniform.utype,
+                    size: uniform.size,
+                })
+                .collect()
+        };
+
+        let attributes = unsafe {
+            (0..self.get_active_attributes(program))
+                .filter_map(|index| self.get_active_attribute(program, index))
+                .map(|attribute| AttributeInfo {
+                    location: self.get_attrib_location(program, &attribute.name),
+                    name: attribute.name,
+                    ty: attribute.atype,
+                    size: attribute.size,
+                })
+                .collect()
+        };
+
+        // glow doesn't wrap glValidateProgram or glGetProgramiv
+        type ValidateProgram = unsafe extern "system" fn(u32);
+        type GetProgramIv = unsafe extern "system" fn(u32, u32, *mut i32);
+        let valid = self
+            .proc_address("glValidateProgram")
+            .zip(self.proc_address("glGetProgramiv"))
+            .map(|(validate_program, get_program_iv)| unsafe {
+                let validate_program: ValidateProgram = std::mem::transmute(validate_program);
+                let get_program_iv: GetProgramIv = std::mem::transmute(get_program_iv);
+
+                let mut status = 0;
+                validate_program(program.0.get());
+                get_program_iv(program.0.get(), gl::VALIDATE_STATUS, &mut status);
+                status != 0
+            });
+        let validate_log = unsafe { self.get_program_info_log(program) };
+        self.check_error("program_introspection");
+
+        ProgramInfo {
+            uniforms,
+            attributes,
+            valid,
+            validate_log,
+        }
+    }
+}
+
+struct ProgramInner {
+    glow: Arc<Context>,
+    program: Cell<NativeProgram>,
+}
+
+impl Drop for ProgramInner {
+    fn drop(&mut self) {
+        unsafe { self.glow.delete_program(self.program.get()) };
+    }
+}
+
+/// A shader program whose underlying GL program can be replaced, e.g. when reloading shaders.
+///
+/// Clones share the same program, so a replacement through one is seen by all of them. Look the
+/// program up with [Program::raw] or bind it with [Program::bind] each time it's drawn with,
+/// rather than holding on to the raw program. The program is deleted when the last clone is
+/// dropped.
+#[derive(Clone)]
+pub struct Program {
+    inner: Rc<ProgramInner>,
+}
+
+impl Program {
+    /// Take ownership of a linked program.
+    pub fn new(ezgl: &EzglHandle, program: NativeProgram) -> Self {
+        Self {
+            inner: Rc::new(ProgramInner {
+                glow: ezgl.glow_context(),
+                program: Cell::new(program),
+            }),
+        }
+    }
+
+    /// Build a program from vertex and fragment shader sources, as with
+    /// [EzglHandle::program_from_sources].
+    pub fn from_sources(
+        ezgl: &EzglHandle,
+        vertex: &str,
+        fragment: &str,
+    ) -> Result<Self, ProgramError> {
+        Ok(Self::new(
+            ezgl,
+            ezgl.program_from_sources(vertex, fragment)?,
+        ))
+    }
+
+    /// Build a program from a single source, as with [EzglHandle::program_from_combined_source].
+    pub fn from_combined_source(ezgl: &EzglHandle, source: &str) -> Result<Self, ProgramError> {
+        Ok(Self::new(ezgl, ezgl.program_from_combined_source(source)?))
+    }
+
+    /// Build a compute program, as with [EzglHandle::program_from_compute_source].
+    pub fn from_compute_source(ezgl: &EzglHandle, source: &str) -> Result<Self, ProgramError> {
+        Ok(Self::new(ezgl, ezgl.program_from_compute_source(source)?))
+    }
+
+    /// Replace the program, deleting the old one.
+    pub fn replace(&self, program: NativeProgram) {
+        let old = self.inner.program.replace(program);
+        unsafe { self.inner.glow.delete_program(old) };
+    }
+
+    /// Make the program current.
+    pub fn bind(&self, ezgl: &EzglHandle) {
+        unsafe { ezgl.use_program(Some(self.raw())) };
+        ezgl.check_error("Program::bind");
+
+        #[cfg(feature = "state-cache")]
+        ezgl.state_cache.set_program(Some(self.raw()));
+    }
+
+    /// Get the current raw GL program.
+    pub fn raw(&self) -> NativeProgram {
+        self.inner.program.get()
+    }
+}
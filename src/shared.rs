@@ -0,0 +1,182 @@
+//! Shared contexts for uploading resources from another thread.
+//!
+//! GL objects like textures and buffers can be shared between contexts in the same share group,
+//! with each context current on at most one thread. [Ezgl::new_shared_headless] creates a context
+//! in the share group of an [Ezgl] that draws to a small offscreen pbuffer instead of a window,
+//! so a worker thread can make it current and upload resources for the main thread to use.
+//!
+//! ```no_run
+//! # let ezgl: ezgl::Ezgl = todo!();
+//! use ezgl::gl::HasContext;
+//!
+//! let shared = ezgl.new_shared_headless(1, 1).unwrap();
+//! let worker = std::thread::spawn(move || {
+//!     let shared = shared.make_current().unwrap();
+//!     let texture = unsafe { shared.create_texture().unwrap() };
+//!     // ... upload the texture ...
+//!
+//!     // the upload has to be finished before the main thread uses the texture
+//!     unsafe { shared.finish() };
+//!     texture
+//! });
+//!
+//! let texture = worker.join().unwrap();
+//! unsafe { ezgl.bind_texture(ezgl::gl::TEXTURE_2D, Some(texture)) };
+//! ```
+//!
+//! Container objects like vertex arrays and framebuffers are not shared, so they must be created
+//! by the context that uses them.
+
+use crate::{
+    default_debug_callback, gl::Context, install_debug_callback, Error, Ezgl, EzglHandle, Result,
+};
+use glutin::{
+    config::{Config, ConfigSurfaceTypes, ConfigTemplateBuilder, GlConfig},
+    context::{
+        ContextAttributesBuilder, GlContext, NotCurrentContext, NotCurrentGlContext,
+        PossiblyCurrentContext, PossiblyCurrentGlContext,
+    },
+    display::{Display, GlDisplay},
+    surface::{PbufferSurface, Surface, SurfaceAttributesBuilder},
+};
+use std::{num::NonZeroU32, sync::Arc};
+
+impl Ezgl {
+    /// Create a context in the same share group as this one, backed by a pbuffer surface.
+    ///
+    /// The context is not current, and can be sent to another thread and made current there with
+    /// [SharedContext::make_current]. The configuration of this [Ezgl] is reused if it supports
+    /// pbuffers, otherwise another configuration is chosen, and this returns
+    /// [Error::Unsupported] if there is none. The context has the same
+    /// [release behavior](crate::EzglBuilder::release_behavior) as this one, so a worker can skip
+    /// the flush when it releases the context. A `width` or `height` of zero is treated as 1.
+    pub fn new_shared_headless(&self, width: u32, height: u32) -> Result<SharedContext> {
+        let display = self.handle.display.clone();
+
+        let config = if self
+            .config
+            .config_surface_types()
+            .contains(ConfigSurfaceTypes::PBUFFER)
+        {
+            self.config.clone()
+        } else {
+            let template = ConfigTemplateBuilder::new()
+                .with_alpha_size(8)
+                .with_surface_type(ConfigSurfaceTypes::PBUFFER)
+                .build();
+            unsafe { display.find_configs(template)? }
+                .next()
+                .ok_or(Error::Unsupported("pbuffer config"))?
+        };
+
+        let attributes = SurfaceAttributesBuilder::<PbufferSurface>::new().build(
+            NonZeroU32::new(width.max(1)).unwrap(),
+            NonZeroU32::new(height.max(1)).unwrap(),
+        );
+        let surface = unsafe { display.create_pbuffer_surface(&config, &attributes)? };
+
+        let context_attributes = ContextAttributesBuilder::new()
+            .with_context_api(self.glutin.context_api())
+            .with_sharing(&self.glutin)
+            .with_release_behavior(self.release_behavior)
+            .build(None);
+        let context = unsafe { display.create_context(&config, &context_attributes)? };
+
+        Ok(SharedContext {
+            context,
+            surface,
+            display,
+            config,
+            check_errors: self.handle.check_errors,
+        })
+    }
+}
+
+/// A headless context in the share group of an [Ezgl], which is not current on any thread.
+///
+/// Created with [Ezgl::new_shared_headless]. This can be sent to another thread.
+pub struct SharedContext {
+    context: NotCurrentContext,
+    surface: Surface<PbufferSurface>,
+    display: Display,
+    config: Config,
+    check_errors: bool,
+}
+
+impl SharedContext {
+    /// Make the context current on this thread.
+    ///
+    /// GL functions are loaded for the context, and the default debug callback is installed.
+    pub fn make_current(self) -> Result<CurrentSharedContext> {
+        let glutin = self.context.make_current(&self.surface)?;
+
+        let mut glow = unsafe {
+            Context::from_loader_function(|symbol| {
+                let cstring = std::ffi::CString::new(symbol).unwrap();
+                self.display.get_proc_address(&cstring)
+            })
+        };
+
+        let debug_callback = install_debug_callback(&mut glow, Box::new(default_debug_callback));
+
+        #[allow(clippy::arc_with_non_send_sync)]
+        let glow = Arc::new(glow);
+
+        Ok(CurrentSharedContext {
+            handle: EzglHandle::new(glow, self.display, debug_callback, self.check_errors),
+            glutin,
+            surface: self.surface,
+            config: self.config,
+        })
+    }
+
+    /// Get the configuration the context was created with.
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+}
+
+/// A headless shared context that is current on this thread.
+///
+/// This type implements Deref into [EzglHandle]. Any [EzglHandle]s cloned from it must be
+/// dropped before calling [CurrentSharedContext::make_not_current].
+pub struct CurrentSharedContext {
+    handle: EzglHandle,
+    glutin: PossiblyCurrentContext,
+    surface: Surface<PbufferSurface>,
+    config: Config,
+}
+
+impl CurrentSharedContext {
+    /// Make the context not current, so it can be sent to another thread.
+    pub fn make_not_current(self) -> Result<SharedContext> {
+        let display = self.handle.display.clone();
+        let check_errors = self.handle.check_errors;
+        drop(self.handle);
+
+        Ok(SharedContext {
+            context: self.glutin.make_not_current()?,
+            surface: self.surface,
+            display,
+            config: self.config,
+            check_errors,
+        })
+    }
+
+    /// Get a cloneable handle to the context.
+    pub fn handle(&self) -> EzglHandle {
+        self.handle.clone()
+    }
+
+    /// Get the (possibly) current glutin context.
+    pub fn glutin(&self) -> &PossiblyCurrentContext {
+        &self.glutin
+    }
+}
+
+impl std::ops::Deref for CurrentSharedContext {
+    type Target = EzglHandle;
+    fn deref(&self) -> &Self::Target {
+        &self.handle
+    }
+}
@@ -0,0 +1,118 @@
+//! Redundant bind elimination, enabled with `feature = "state-cache"`.
+
+use crate::{
+    gl::{self, HasContext, NativeProgram, NativeTexture, NativeVertexArray},
+    EzglHandle,
+};
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+};
+
+/// Tracks GL binding state set through the `*_cached` methods on [EzglHandle], and by the crate's
+/// own helpers that leave something bound.
+///
+/// A None value means the state is unknown, so the next bind always goes through to GL.
+#[derive(Default)]
+pub(crate) struct StateCache {
+    program: Cell<Option<Option<NativeProgram>>>,
+    vertex_array: Cell<Option<Option<NativeVertexArray>>>,
+    active_texture: Cell<Option<u32>>,
+    textures: RefCell<HashMap<(u32, u32), Option<NativeTexture>>>,
+    calls: Cell<u64>,
+}
+
+impl StateCache {
+    fn invalidate(&self) {
+        self.program.set(None);
+        self.vertex_array.set(None);
+        self.active_texture.set(None);
+        self.textures.borrow_mut().clear();
+    }
+
+    /// Record a program made current by a helper.
+    pub(crate) fn set_program(&self, program: Option<NativeProgram>) {
+        self.program.set(Some(program));
+    }
+
+    /// Record a vertex array bound by a helper.
+    pub(crate) fn set_vertex_array(&self, vertex_array: Option<NativeVertexArray>) {
+        self.vertex_array.set(Some(vertex_array));
+    }
+
+    /// Record a texture bound to `target` on `unit`, e.g. [gl::TEXTURE0], by a helper.
+    pub(crate) fn set_texture(&self, unit: u32, target: u32, texture: Option<NativeTexture>) {
+        self.textures.borrow_mut().insert((unit, target), texture);
+    }
+}
+
+impl EzglHandle {
+    /// Call [HasContext::use_program] if `program` is not already in use.
+    ///
+    /// The `*_cached` methods only know about state changed through them and through this crate's
+    /// helpers. After binding state directly, or after handing the context to other code, call
+    /// [EzglHandle::invalidate_state_cache].
+    pub fn use_program_cached(&self, program: Option<NativeProgram>) {
+        if self.state_cache.program.get() != Some(program) {
+            unsafe { self.use_program(program) };
+            self.check_error("use_program_cached");
+            self.state_cache.calls.set(self.state_cache.calls.get() + 1);
+            self.state_cache.program.set(Some(program));
+        }
+    }
+
+    /// Call [HasContext::bind_vertex_array] if `vertex_array` is not already bound.
+    pub fn bind_vertex_array_cached(&self, vertex_array: Option<NativeVertexArray>) {
+        if self.state_cache.vertex_array.get() != Some(vertex_array) {
+            unsafe { self.bind_vertex_array(vertex_array) };
+            self.check_error("bind_vertex_array_cached");
+            self.state_cache.calls.set(self.state_cache.calls.get() + 1);
+            self.state_cache.vertex_array.set(Some(vertex_array));
+        }
+    }
+
+    /// Call [HasContext::active_texture] if `unit` is not already the active texture unit.
+    ///
+    /// `unit` is a texture unit enum, e.g. [gl::TEXTURE0].
+    pub fn active_texture_cached(&self, unit: u32) {
+        if self.state_cache.active_texture.get() != Some(unit) {
+            unsafe { self.active_texture(unit) };
+            self.check_error("active_texture_cached");
+            self.state_cache.calls.set(self.state_cache.calls.get() + 1);
+            self.state_cache.active_texture.set(Some(unit));
+        }
+    }
+
+    /// Call [HasContext::bind_texture] if `texture` is not already bound to `target` on the active
+    /// texture unit.
+    ///
+    /// If the active texture unit is unknown it is reset to [gl::TEXTURE0].
+    pub fn bind_texture_cached(&self, target: u32, texture: Option<NativeTexture>) {
+        let unit = match self.state_cache.active_texture.get() {
+            Some(unit) => unit,
+            None => {
+                self.active_texture_cached(gl::TEXTURE0);
+                gl::TEXTURE0
+            }
+        };
+
+        let mut textures = self.state_cache.textures.borrow_mut();
+        if textures.get(&(unit, target)) != Some(&texture) {
+            unsafe { self.bind_texture(target, texture) };
+            self.check_error("bind_texture_cached");
+            self.state_cache.calls.set(self.state_cache.calls.get() + 1);
+            textures.insert((unit, target), texture);
+        }
+    }
+
+    /// Forget all binding state tracked by the `*_cached` methods.
+    pub fn invalidate_state_cache(&self) {
+        self.state_cache.invalidate();
+    }
+
+    /// Get how many GL calls the `*_cached` methods have made, e.g. to measure how many binds the
+    /// cache skipped.
+    pub fn state_cache_calls(&self) -> u64 {
+        self.state_cache.calls.get()
+    }
+}
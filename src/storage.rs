@@ -0,0 +1,147 @@
+//! Typed shader storage buffer objects, enabled with `feature = "bytemuck"`.
+
+use crate::{
+    gl::{self, Context, HasContext, NativeBuffer},
+    Error, EzglHandle, Result,
+};
+use bytemuck::Pod;
+use std::{cell::Cell, marker::PhantomData, sync::Arc};
+
+/// A shader storage buffer holding an array of `T`, for reading and writing from shaders.
+///
+/// `T` must match the std430 layout of the buffer block in the shader. Unlike std140, arrays of
+/// scalars and `vec2`s are tightly packed, but `vec3` is still 16-byte aligned. Requires GL 4.3,
+/// GLES 3.1, or `ARB_shader_storage_buffer_object`.
+///
+/// ```no_run
+/// # let ezgl: ezgl::Ezgl = todo!();
+/// use ezgl::{compute::MemoryBarrier, shader::Program, storage::StorageBuffer};
+///
+/// let program = Program::from_compute_source(
+///     &ezgl,
+///     "#version 430
+///     layout(local_size_x = 64) in;
+///     layout(std430, binding = 0) buffer Data { float values[]; };
+///     void main() { values[gl_GlobalInvocationID.x] *= 2.0; }",
+/// )
+/// .unwrap();
+///
+/// let input = (0..256).map(|i| i as f32).collect::<Vec<_>>();
+/// let buffer = StorageBuffer::new(&ezgl, &input).unwrap();
+/// buffer.bind(&ezgl, 0);
+///
+/// program.bind(&ezgl);
+/// ezgl.dispatch_compute(input.len() as u32 / 64, 1, 1).unwrap();
+/// ezgl.memory_barrier(MemoryBarrier::BUFFER_UPDATE).unwrap();
+///
+/// let output = buffer.read_back(&ezgl).unwrap();
+/// assert!(input.iter().zip(output).all(|(a, b)| a * 2.0 == b));
+/// ```
+///
+/// The buffer is deleted when this is dropped.
+pub struct StorageBuffer<T: Pod> {
+    glow: Arc<Context>,
+    buffer: NativeBuffer,
+    len: Cell<usize>,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Pod> StorageBuffer<T> {
+    /// Create a storage buffer holding `data`.
+    ///
+    /// Returns [Error::Unsupported] if shader storage buffers are unsupported.
+    pub fn new(ezgl: &EzglHandle, data: &[T]) -> Result<Self> {
+        let version = ezgl.version();
+        let supported = ezgl.has_extension("GL_ARB_shader_storage_buffer_object")
+            || if version.is_embedded {
+                (version.major, version.minor) >= (3, 1)
+            } else {
+                (version.major, version.minor) >= (4, 3)
+            };
+        if !supported {
+            return Err(Error::Unsupported("ARB_shader_storage_buffer_object"));
+        }
+
+        let buffer = Self {
+            glow: ezgl.glow_context(),
+            buffer: ezgl.create_buffer_object()?,
+            len: Cell::new(0),
+            _marker: PhantomData,
+        };
+        buffer.upload(ezgl, data);
+
+        Ok(buffer)
+    }
+
+    /// Replace the contents of the buffer, resizing it if `data` has a different length.
+    pub fn upload(&self, ezgl: &EzglHandle, data: &[T]) {
+        if data.len() == self.len.get() {
+            ezgl.update_buffer(
+                gl::SHADER_STORAGE_BUFFER,
+                self.buffer,
+                0,
+                bytemuck::cast_slice(data),
+            );
+        } else {
+            ezgl.upload_buffer(
+                gl::SHADER_STORAGE_BUFFER,
+                self.buffer,
+                bytemuck::cast_slice(data),
+                gl::DYNAMIC_COPY,
+            );
+            self.len.set(data.len());
+        }
+        ezgl.check_error("StorageBuffer::upload");
+    }
+
+    /// Bind the buffer to a shader storage binding point, e.g. `binding = 0` in the shader.
+    pub fn bind(&self, ezgl: &EzglHandle, binding: u32) {
+        unsafe { ezgl.bind_buffer_base(gl::SHADER_STORAGE_BUFFER, binding, Some(self.buffer)) };
+        ezgl.check_error("StorageBuffer::bind");
+    }
+
+    /// Read the contents of the buffer back from the GPU.
+    ///
+    /// This stalls until the GPU has finished writing the buffer. Issue a
+    /// [MemoryBarrier::BUFFER_UPDATE](crate::compute::MemoryBarrier::BUFFER_UPDATE) barrier first
+    /// if a shader wrote to it. Returns [Error::Gl] if the buffer could not be mapped.
+    pub fn read_back(&self, ezgl: &EzglHandle) -> Result<Vec<T>> {
+        let mut data = vec![T::zeroed(); self.len.get()];
+        if data.is_empty() {
+            return Ok(data);
+        }
+
+        let mapped = ezgl.copy_from_buffer(
+            gl::SHADER_STORAGE_BUFFER,
+            self.buffer,
+            bytemuck::cast_slice_mut(&mut data),
+        );
+        ezgl.check_error("StorageBuffer::read_back");
+
+        if !mapped {
+            return Err(Error::Gl("Could not map storage buffer".into()));
+        }
+        Ok(data)
+    }
+
+    /// Get the number of elements in the buffer.
+    pub fn len(&self) -> usize {
+        self.len.get()
+    }
+
+    /// Check whether the buffer has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len.get() == 0
+    }
+
+    /// Get the raw GL buffer.
+    pub fn raw(&self) -> NativeBuffer {
+        self.buffer
+    }
+}
+
+impl<T: Pod> Drop for StorageBuffer<T> {
+    fn drop(&mut self) {
+        unsafe { self.glow.delete_buffer(self.buffer) };
+    }
+}
@@ -0,0 +1,330 @@
+//! Texture storage, parameters, anisotropic filtering, compressed formats, and sampler objects.
+
+use crate::{
+    gl::{self, Context, HasContext, NativeSampler, NativeTexture},
+    Error, EzglHandle, Result,
+};
+use std::sync::Arc;
+
+/// Filter used when a texture is minified, i.e. drawn smaller than its size.
+///
+/// The mipmap filters need the texture to have mipmaps, otherwise it is incomplete and samples as
+/// black.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MinFilter {
+    Nearest,
+    #[default]
+    Linear,
+    NearestMipmapNearest,
+    LinearMipmapNearest,
+    NearestMipmapLinear,
+    LinearMipmapLinear,
+}
+
+impl MinFilter {
+    fn to_gl(self) -> u32 {
+        match self {
+            MinFilter::Nearest => gl::NEAREST,
+            MinFilter::Linear => gl::LINEAR,
+            MinFilter::NearestMipmapNearest => gl::NEAREST_MIPMAP_NEAREST,
+            MinFilter::LinearMipmapNearest => gl::LINEAR_MIPMAP_NEAREST,
+            MinFilter::NearestMipmapLinear => gl::NEAREST_MIPMAP_LINEAR,
+            MinFilter::LinearMipmapLinear => gl::LINEAR_MIPMAP_LINEAR,
+        }
+    }
+}
+
+/// Filter used when a texture is magnified, i.e. drawn larger than its size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MagFilter {
+    Nearest,
+    #[default]
+    Linear,
+}
+
+impl MagFilter {
+    fn to_gl(self) -> u32 {
+        match self {
+            MagFilter::Nearest => gl::NEAREST,
+            MagFilter::Linear => gl::LINEAR,
+        }
+    }
+}
+
+/// How texture coordinates outside of `0..1` are handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Wrap {
+    Repeat,
+    MirroredRepeat,
+    #[default]
+    ClampToEdge,
+    /// Not available on GLES 3.0.
+    ClampToBorder,
+}
+
+impl Wrap {
+    fn to_gl(self) -> u32 {
+        match self {
+            Wrap::Repeat => gl::REPEAT,
+            Wrap::MirroredRepeat => gl::MIRRORED_REPEAT,
+            Wrap::ClampToEdge => gl::CLAMP_TO_EDGE,
+            Wrap::ClampToBorder => gl::CLAMP_TO_BORDER,
+        }
+    }
+}
+
+/// Filter and wrap modes for [EzglHandle::set_texture_params].
+///
+/// The default is linear filtering without mipmaps and clamping to the edge, which makes any
+/// texture complete as soon as its base level is uploaded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TextureParams {
+    pub min_filter: MinFilter,
+    pub mag_filter: MagFilter,
+    pub wrap_s: Wrap,
+    pub wrap_t: Wrap,
+    /// Only set if Some, since only 3D textures and cube maps have an R coordinate.
+    pub wrap_r: Option<Wrap>,
+}
+
+impl TextureParams {
+    /// The GL parameter names and values to set.
+    fn parameters(&self) -> impl Iterator<Item = (u32, i32)> {
+        [
+            Some((gl::TEXTURE_MIN_FILTER, self.min_filter.to_gl())),
+            Some((gl::TEXTURE_MAG_FILTER, self.mag_filter.to_gl())),
+            Some((gl::TEXTURE_WRAP_S, self.wrap_s.to_gl())),
+            Some((gl::TEXTURE_WRAP_T, self.wrap_t.to_gl())),
+            self.wrap_r
+                .map(|wrap_r| (gl::TEXTURE_WRAP_R, wrap_r.to_gl())),
+        ]
+        .into_iter()
+        .flatten()
+        .map(|(name, value)| (name, value as i32))
+    }
+}
+
+impl EzglHandle {
+    /// Set the filter and wrap modes of the texture currently bound to `target`.
+    ///
+    /// GL's default min filter uses mipmaps, so a texture without them that never has its min
+    /// filter set is incomplete and samples as black. Setting everything at once with
+    /// [TextureParams::default] avoids that.
+    pub fn set_texture_params(&self, target: u32, params: TextureParams) {
+        for (name, value) in params.parameters() {
+            unsafe { self.tex_parameter_i32(target, name, value) };
+        }
+        self.check_error("set_texture_params");
+    }
+
+    /// Create a texture with immutable storage for `levels` mip levels.
+    ///
+    /// The size and format can't change afterward, which lets the driver skip completeness checks
+    /// and is required for texture views. Upload the contents with
+    /// [HasContext::tex_sub_image_2d]. `target` is `TEXTURE_2D`, `TEXTURE_CUBE_MAP`,
+    /// `TEXTURE_1D_ARRAY`, or `TEXTURE_RECTANGLE`, and `internal_format` must be sized, e.g.
+    /// [gl::RGBA8]. The texture gets [TextureParams::default], except with
+    /// [MinFilter::LinearMipmapLinear] if it has more than one level. Uses direct state access if
+    /// [EzglHandle::uses_dsa], otherwise the previous binding of `target` is restored afterward.
+    ///
+    /// Returns [Error::Unsupported] without GL 4.2, GLES 3.0, or `ARB_texture_storage`.
+    pub fn create_texture_storage(
+        &self,
+        target: u32,
+        levels: i32,
+        internal_format: u32,
+        width: i32,
+        height: i32,
+    ) -> Result<NativeTexture> {
+        let version = self.version();
+        let supported = self.has_extension("GL_ARB_texture_storage")
+            || if version.is_embedded {
+                version.major >= 3
+            } else {
+                (version.major, version.minor) >= (4, 2)
+            };
+        if !supported {
+            return Err(Error::Unsupported("ARB_texture_storage"));
+        }
+
+        let params = TextureParams {
+            min_filter: if levels > 1 {
+                MinFilter::LinearMipmapLinear
+            } else {
+                MinFilter::Linear
+            },
+            ..Default::default()
+        };
+
+        let texture = self.create_texture_for_target(target)?;
+        self.allocate_texture_storage_2d(target, texture, levels, internal_format, width, height);
+        for (name, value) in params.parameters() {
+            self.set_texture_parameter(target, texture, name, value);
+        }
+        self.check_error("create_texture_storage");
+
+        Ok(texture)
+    }
+
+    /// Get the greatest anisotropic filtering level supported.
+    ///
+    /// Returns None without GL 4.6, `ARB_texture_filter_anisotropic`, or
+    /// `EXT_texture_filter_anisotropic`.
+    pub fn max_anisotropy(&self) -> Option<f32> {
+        let version = self.version();
+        let supported = (!version.is_embedded && (version.major, version.minor) >= (4, 6))
+            || self.has_extension("GL_ARB_texture_filter_anisotropic")
+            || self.has_extension("GL_EXT_texture_filter_anisotropic");
+
+        supported.then(|| unsafe { self.get_parameter_f32(gl::MAX_TEXTURE_MAX_ANISOTROPY) })
+    }
+
+    /// Set the anisotropic filtering level of the texture currently bound to `target`.
+    ///
+    /// 1 disables anisotropic filtering, and higher levels sharpen textures viewed at grazing
+    /// angles. `level` is clamped to [EzglHandle::max_anisotropy]. Returns [Error::Unsupported] if
+    /// anisotropic filtering is unsupported.
+    pub fn set_texture_anisotropy(&self, target: u32, level: f32) -> Result<()> {
+        let max = self
+            .max_anisotropy()
+            .ok_or(Error::Unsupported("EXT_texture_filter_anisotropic"))?;
+
+        unsafe {
+            self.tex_parameter_f32(target, gl::TEXTURE_MAX_ANISOTROPY, level.clamp(1.0, max));
+        }
+        self.check_error("set_texture_anisotropy");
+
+        Ok(())
+    }
+
+    /// Get the preferred compressed internal format for uploading textures, or None if none is
+    /// supported and textures should be uploaded uncompressed.
+    ///
+    /// BC7 (`COMPRESSED_RGBA_BPTC_UNORM`) is preferred, then BC3 (`COMPRESSED_RGBA_S3TC_DXT5_EXT`)
+    /// if `has_alpha` or BC1 (`COMPRESSED_RGB_S3TC_DXT1_EXT`) if not, then ETC2
+    /// (`COMPRESSED_RGBA8_ETC2_EAC` or `COMPRESSED_RGB8_ETC2`). A format counts as supported if
+    /// it's listed in `COMPRESSED_TEXTURE_FORMATS` or the version or an extension guarantees it,
+    /// since drivers don't always list everything they support.
+    pub fn best_compressed_format(&self, has_alpha: bool) -> Option<u32> {
+        const COMPRESSED_RGB_S3TC_DXT1_EXT: u32 = 0x83F0;
+        const COMPRESSED_RGBA_S3TC_DXT5_EXT: u32 = 0x83F3;
+
+        let listed = unsafe {
+            let count = self.get_parameter_i32(gl::NUM_COMPRESSED_TEXTURE_FORMATS);
+            let mut listed = vec![0; count.max(0) as usize];
+            if !listed.is_empty() {
+                self.get_parameter_i32_slice(gl::COMPRESSED_TEXTURE_FORMATS, &mut listed);
+            }
+            listed
+        };
+
+        let version = self.version();
+        let desktop =
+            |major, minor| !version.is_embedded && (version.major, version.minor) >= (major, minor);
+        let bptc = desktop(4, 2)
+            || self.has_extension("GL_ARB_texture_compression_bptc")
+            || self.has_extension("GL_EXT_texture_compression_bptc");
+        let s3tc = self.has_extension("GL_EXT_texture_compression_s3tc");
+        let etc2 = (version.is_embedded && version.major >= 3)
+            || desktop(4, 3)
+            || self.has_extension("GL_ARB_ES3_compatibility");
+
+        let candidates = if has_alpha {
+            [
+                (gl::COMPRESSED_RGBA_BPTC_UNORM, bptc),
+                (COMPRESSED_RGBA_S3TC_DXT5_EXT, s3tc),
+                (gl::COMPRESSED_RGBA8_ETC2_EAC, etc2),
+            ]
+        } else {
+            [
+                (gl::COMPRESSED_RGBA_BPTC_UNORM, bptc),
+                (COMPRESSED_RGB_S3TC_DXT1_EXT, s3tc),
+                (gl::COMPRESSED_RGB8_ETC2, etc2),
+            ]
+        };
+
+        candidates
+            .into_iter()
+            .find(|&(format, guaranteed)| guaranteed || listed.contains(&(format as i32)))
+            .map(|(format, _)| format)
+    }
+}
+
+/// A sampler object, which holds filter and wrap modes separately from any texture.
+///
+/// A sampler bound to a texture unit overrides the parameters of whatever texture is bound to the
+/// unit, so one sampler can be shared by many textures. Requires GL 3.3 or GLES 3.0.
+///
+/// ```no_run
+/// # let ezgl: ezgl::Ezgl = todo!();
+/// # let texture: ezgl::gl::NativeTexture = todo!();
+/// use ezgl::{gl, texture::{MagFilter, Sampler, TextureParams}};
+///
+/// let pixelated = Sampler::new(
+///     &ezgl,
+///     TextureParams {
+///         mag_filter: MagFilter::Nearest,
+///         ..Default::default()
+///     },
+/// )
+/// .unwrap();
+///
+/// ezgl.bind_texture_to_unit(0, gl::TEXTURE_2D, texture);
+/// pixelated.bind_to_unit(&ezgl, 0);
+/// ```
+///
+/// The sampler is deleted when this is dropped.
+pub struct Sampler {
+    glow: Arc<Context>,
+    sampler: NativeSampler,
+}
+
+impl Sampler {
+    /// Create a sampler with the given filter and wrap modes.
+    ///
+    /// Returns [Error::Unsupported] without GL 3.3, GLES 3.0, or `ARB_sampler_objects`.
+    pub fn new(ezgl: &EzglHandle, params: TextureParams) -> Result<Self> {
+        let version = ezgl.version();
+        let supported = ezgl.has_extension("GL_ARB_sampler_objects")
+            || if version.is_embedded {
+                version.major >= 3
+            } else {
+                (version.major, version.minor) >= (3, 3)
+            };
+        if !supported {
+            return Err(Error::Unsupported("ARB_sampler_objects"));
+        }
+
+        let sampler = Self {
+            glow: ezgl.glow_context(),
+            sampler: unsafe { ezgl.create_sampler()? },
+        };
+
+        for (name, value) in params.parameters() {
+            unsafe { ezgl.sampler_parameter_i32(sampler.sampler, name, value) };
+        }
+        ezgl.check_error("Sampler::new");
+
+        Ok(sampler)
+    }
+
+    /// Bind the sampler to a texture unit.
+    ///
+    /// `unit` is the index of the unit, not `TEXTURE0 + unit`, as in
+    /// [EzglHandle::bind_texture_to_unit].
+    pub fn bind_to_unit(&self, ezgl: &EzglHandle, unit: u32) {
+        unsafe { ezgl.bind_sampler(unit, Some(self.sampler)) };
+        ezgl.check_error("Sampler::bind_to_unit");
+    }
+
+    /// Get the raw GL sampler.
+    pub fn raw(&self) -> NativeSampler {
+        self.sampler
+    }
+}
+
+impl Drop for Sampler {
+    fn drop(&mut self) {
+        unsafe { self.glow.delete_sampler(self.sampler) };
+    }
+}
@@ -0,0 +1,110 @@
+//! Capturing vertex shader outputs into buffers.
+
+use crate::{
+    gl::{self, Context, HasContext, NativeBuffer, NativeTransformFeedback},
+    native_transform_feedback, Error, EzglHandle, Result,
+};
+use std::sync::Arc;
+
+/// A transform feedback object, which records which buffers captured varyings are written to.
+///
+/// The program drawn with must be linked with the varyings to capture, using
+/// [EzglHandle::link_program_with_varyings]. Requires GL 4.0, GLES 3.0, or
+/// `ARB_transform_feedback2`.
+///
+/// ```no_run
+/// # let ezgl: ezgl::Ezgl = todo!();
+/// # let (vertex, fragment, particles): (ezgl::gl::NativeShader, ezgl::gl::NativeShader, ezgl::gl::NativeBuffer) = todo!();
+/// use ezgl::{gl::{self, HasContext}, transform_feedback::TransformFeedback};
+///
+/// let program = ezgl
+///     .link_program_with_varyings(&[vertex, fragment], &["out_position"], gl::INTERLEAVED_ATTRIBS)
+///     .unwrap();
+/// let feedback = TransformFeedback::new(&ezgl).unwrap();
+/// feedback.bind_buffer(&ezgl, 0, particles);
+///
+/// unsafe {
+///     ezgl.use_program(Some(program));
+///     ezgl.enable(gl::RASTERIZER_DISCARD);
+/// }
+/// feedback.capture(&ezgl, gl::POINTS, || unsafe { ezgl.draw_arrays(gl::POINTS, 0, 1024) });
+/// unsafe { ezgl.disable(gl::RASTERIZER_DISCARD) };
+/// ```
+///
+/// The transform feedback object is deleted when this is dropped, but not its buffers.
+pub struct TransformFeedback {
+    glow: Arc<Context>,
+    feedback: NativeTransformFeedback,
+}
+
+impl TransformFeedback {
+    /// Create a transform feedback object with no buffers bound.
+    ///
+    /// Returns [Error::Unsupported] if transform feedback objects are unsupported.
+    pub fn new(ezgl: &EzglHandle) -> Result<Self> {
+        let version = ezgl.version();
+        let supported = ezgl.has_extension("GL_ARB_transform_feedback2")
+            || if version.is_embedded {
+                version.major >= 3
+            } else {
+                version.major >= 4
+            };
+        if !supported {
+            return Err(Error::Unsupported("ARB_transform_feedback2"));
+        }
+
+        let feedback = unsafe { ezgl.create_transform_feedback()? };
+        ezgl.check_error("TransformFeedback::new");
+
+        Ok(Self {
+            glow: ezgl.glow_context(),
+            feedback,
+        })
+    }
+
+    /// Write the varying at `index` to `buffer`, or every varying with
+    /// [INTERLEAVED_ATTRIBS](gl::INTERLEAVED_ATTRIBS), which only uses index 0.
+    ///
+    /// The buffer must be large enough for everything captured. The previous transform feedback
+    /// binding is restored afterward.
+    pub fn bind_buffer(&self, ezgl: &EzglHandle, index: u32, buffer: NativeBuffer) {
+        self.with_bound(ezgl, || unsafe {
+            ezgl.bind_buffer_base(gl::TRANSFORM_FEEDBACK_BUFFER, index, Some(buffer));
+        });
+        ezgl.check_error("TransformFeedback::bind_buffer");
+    }
+
+    /// Capture the varyings of everything drawn in `f` into the bound buffers.
+    ///
+    /// `primitive` is [gl::POINTS], [gl::LINES], or [gl::TRIANGLES], and the draws in `f` must
+    /// use a matching mode. Enable [gl::RASTERIZER_DISCARD] to skip rasterizing if only the
+    /// captured data is needed. The previous transform feedback binding is restored afterward.
+    pub fn capture<F: FnOnce()>(&self, ezgl: &EzglHandle, primitive: u32, f: F) {
+        self.with_bound(ezgl, || unsafe {
+            ezgl.begin_transform_feedback(primitive);
+            f();
+            ezgl.end_transform_feedback();
+        });
+        ezgl.check_error("TransformFeedback::capture");
+    }
+
+    fn with_bound<F: FnOnce()>(&self, ezgl: &EzglHandle, f: F) {
+        unsafe {
+            let prev = ezgl.get_parameter_i32(gl::TRANSFORM_FEEDBACK_BINDING);
+            ezgl.bind_transform_feedback(gl::TRANSFORM_FEEDBACK, Some(self.feedback));
+            f();
+            ezgl.bind_transform_feedback(gl::TRANSFORM_FEEDBACK, native_transform_feedback(prev));
+        }
+    }
+
+    /// Get the raw GL transform feedback object.
+    pub fn raw(&self) -> NativeTransformFeedback {
+        self.feedback
+    }
+}
+
+impl Drop for TransformFeedback {
+    fn drop(&mut self) {
+        unsafe { self.glow.delete_transform_feedback(self.feedback) };
+    }
+}
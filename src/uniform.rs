@@ -0,0 +1,113 @@
+//! Typed uniform buffer objects, enabled with `feature = "bytemuck"`.
+
+use crate::{
+    gl::{self, Context, HasContext, NativeBuffer},
+    EzglHandle, Result,
+};
+use bytemuck::Pod;
+use std::{marker::PhantomData, sync::Arc};
+
+/// A uniform buffer holding a single `T`, bound to a uniform block binding point.
+///
+/// `T` must match the std140 layout of the uniform block in the shader, which `#[repr(C)]` alone
+/// does not guarantee:
+///
+/// - `float`, `int`, and `uint` are 4-byte aligned, and `vec2` is 8-byte aligned.
+/// - `vec3` and `vec4` are 16-byte aligned, so a `vec3` should be followed by a scalar or padding.
+/// - Every element of an array is 16-byte aligned, even for `float[]`, so use `[[f32; 4]; N]`.
+/// - A `mat4` is four `vec4` columns. A `mat3` is three columns padded to `vec4`.
+/// - The size of the block is rounded up to a multiple of 16 bytes.
+///
+/// ```glsl
+/// layout(std140, binding = 0) uniform Camera {
+///     mat4 view_projection;
+///     vec3 position;
+///     float time;
+/// };
+/// ```
+///
+/// ```
+/// #[repr(C)]
+/// #[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+/// struct Camera {
+///     view_projection: [[f32; 4]; 4],
+///     position: [f32; 3],
+///     time: f32,
+/// }
+/// # assert_eq!(std::mem::size_of::<Camera>() % 16, 0);
+/// ```
+///
+/// The buffer is deleted when this is dropped.
+pub struct UniformBuffer<T: Pod> {
+    glow: Arc<Context>,
+    buffer: NativeBuffer,
+    binding: u32,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Pod> UniformBuffer<T> {
+    /// Create a uniform buffer holding `value` and bind it to `binding`.
+    ///
+    /// In debug builds, panics if the size of `T` is not a multiple of 16 bytes, which usually
+    /// means the struct is missing std140 padding.
+    pub fn new(ezgl: &EzglHandle, binding: u32, value: &T) -> Result<Self> {
+        debug_assert!(
+            std::mem::size_of::<T>() % 16 == 0,
+            "size of {} is {} bytes, which is not a multiple of 16 as std140 requires",
+            std::any::type_name::<T>(),
+            std::mem::size_of::<T>(),
+        );
+
+        unsafe {
+            let buffer = ezgl.create_buffer_object()?;
+            ezgl.upload_buffer(
+                gl::UNIFORM_BUFFER,
+                buffer,
+                bytemuck::bytes_of(value),
+                gl::DYNAMIC_DRAW,
+            );
+            ezgl.bind_buffer_base(gl::UNIFORM_BUFFER, binding, Some(buffer));
+            ezgl.check_error("UniformBuffer::new");
+
+            Ok(Self {
+                glow: ezgl.glow_context(),
+                buffer,
+                binding,
+                _marker: PhantomData,
+            })
+        }
+    }
+
+    /// Replace the contents of the buffer.
+    pub fn update(&self, ezgl: &EzglHandle, value: &T) {
+        ezgl.update_buffer(
+            gl::UNIFORM_BUFFER,
+            self.buffer,
+            0,
+            bytemuck::bytes_of(value),
+        );
+        ezgl.check_error("UniformBuffer::update");
+    }
+
+    /// Bind the buffer to its binding point again, e.g. after another buffer was bound there.
+    pub fn bind(&self, ezgl: &EzglHandle) {
+        unsafe { ezgl.bind_buffer_base(gl::UNIFORM_BUFFER, self.binding, Some(self.buffer)) };
+        ezgl.check_error("UniformBuffer::bind");
+    }
+
+    /// Get the uniform block binding point.
+    pub fn binding(&self) -> u32 {
+        self.binding
+    }
+
+    /// Get the raw GL buffer.
+    pub fn raw(&self) -> NativeBuffer {
+        self.buffer
+    }
+}
+
+impl<T: Pod> Drop for UniformBuffer<T> {
+    fn drop(&mut self) {
+        unsafe { self.glow.delete_buffer(self.buffer) };
+    }
+}
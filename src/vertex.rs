@@ -0,0 +1,132 @@
+//! Vertex attribute layouts.
+
+use crate::{
+    gl::{self, HasContext},
+    EzglHandle,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Attrib {
+    location: u32,
+    size: i32,
+    data_type: u32,
+    normalized: bool,
+    integer: bool,
+    offset: i32,
+}
+
+/// Layout of interleaved vertex attributes in a single buffer.
+///
+/// Attributes are laid out in the order they are added, with no padding between them. The stride
+/// and offsets are computed from the attribute sizes and types.
+///
+/// ```
+/// # use ezgl::{gl, vertex::VertexLayout};
+/// // struct Vertex { position: [f32; 3], color: [u8; 4] }
+/// let layout = VertexLayout::new()
+///     .attrib(0, 3, gl::FLOAT, false)
+///     .attrib(1, 4, gl::UNSIGNED_BYTE, true);
+/// assert_eq!(layout.stride(), 16);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VertexLayout {
+    attribs: Vec<Attrib>,
+    stride: i32,
+}
+
+impl VertexLayout {
+    /// Create an empty layout.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an attribute that is read as floating point in the shader.
+    ///
+    /// `size` is the number of components, and `data_type` is the type of each component in the
+    /// buffer, e.g. [gl::FLOAT]. If `normalized` is true, integer data is mapped to `[0, 1]` or
+    /// `[-1, 1]`.
+    ///
+    /// Panics if `data_type` is not a vertex attribute type.
+    pub fn attrib(self, location: u32, size: i32, data_type: u32, normalized: bool) -> Self {
+        self.push(location, size, data_type, normalized, false)
+    }
+
+    /// Add an attribute that is read as an integer (`int`, `ivec*`, `uint`, `uvec*`) in the shader.
+    ///
+    /// Panics if `data_type` is not an integer vertex attribute type.
+    pub fn attrib_integer(self, location: u32, size: i32, data_type: u32) -> Self {
+        self.push(location, size, data_type, false, true)
+    }
+
+    fn push(
+        mut self,
+        location: u32,
+        size: i32,
+        data_type: u32,
+        normalized: bool,
+        integer: bool,
+    ) -> Self {
+        let bytes = match data_type {
+            gl::BYTE | gl::UNSIGNED_BYTE => size,
+            gl::SHORT | gl::UNSIGNED_SHORT => 2 * size,
+            gl::INT | gl::UNSIGNED_INT => 4 * size,
+            gl::HALF_FLOAT if !integer => 2 * size,
+            gl::FLOAT | gl::FIXED if !integer => 4 * size,
+            gl::DOUBLE if !integer => 8 * size,
+            gl::INT_2_10_10_10_REV
+            | gl::UNSIGNED_INT_2_10_10_10_REV
+            | gl::UNSIGNED_INT_10F_11F_11F_REV
+                if !integer =>
+            {
+                4
+            }
+            _ => panic!("Invalid vertex attribute type {:#x}", data_type),
+        };
+
+        self.attribs.push(Attrib {
+            location,
+            size,
+            data_type,
+            normalized,
+            integer,
+            offset: self.stride,
+        });
+        self.stride += bytes;
+
+        self
+    }
+
+    /// Get the size in bytes of a single vertex.
+    pub fn stride(&self) -> i32 {
+        self.stride
+    }
+
+    /// Set up and enable the attributes on the currently bound vertex array, reading from the
+    /// buffer currently bound to `ARRAY_BUFFER`.
+    pub fn apply(&self, ezgl: &EzglHandle) {
+        for attrib in self.attribs.iter() {
+            unsafe {
+                ezgl.enable_vertex_attrib_array(attrib.location);
+                if attrib.integer {
+                    ezgl.vertex_attrib_pointer_i32(
+                        attrib.location,
+                        attrib.size,
+                        attrib.data_type,
+                        self.stride,
+                        attrib.offset,
+                    );
+                } else {
+                    ezgl.vertex_attrib_pointer_f32(
+                        attrib.location,
+                        attrib.size,
+                        attrib.data_type,
+                        attrib.normalized,
+                        self.stride,
+                        attrib.offset,
+                    );
+                }
+            }
+        }
+        ezgl.check_error("VertexLayout::apply");
+    }
+}
@@ -0,0 +1,147 @@
+//! Shader hot reloading, enabled with `feature = "notify"`.
+
+use crate::{
+    gl::NativeProgram,
+    shader::{Program, ProgramError},
+    EzglHandle,
+};
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use std::{
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver},
+};
+
+type Relink = Box<dyn FnMut(&EzglHandle, &[String]) -> Result<NativeProgram, ProgramError>>;
+
+/// An error from reloading shaders.
+#[derive(Debug)]
+pub enum ReloadError {
+    /// A watched file could not be read. Contains the path and the error.
+    Io(PathBuf, std::io::Error),
+
+    /// The new sources failed to compile or link. The old program is still in use.
+    Program(ProgramError),
+}
+
+impl std::fmt::Display for ReloadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReloadError::Io(path, err) => write!(f, "Could not read {}: {}", path.display(), err),
+            ReloadError::Program(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for ReloadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ReloadError::Io(_, err) => Some(err),
+            ReloadError::Program(err) => Some(err),
+        }
+    }
+}
+
+/// Watches shader files and rebuilds a [Program] when they change.
+///
+/// File events arrive on a background thread, but GL calls must happen on the thread the context
+/// is current on, so changes are only picked up when [ShaderWatcher::poll] is called, e.g. once a
+/// frame. The program is only replaced if the new sources compile and link, so a typo keeps the
+/// last working program on screen.
+///
+/// ```no_run
+/// # use ezgl::{shader::Program, watch::ShaderWatcher};
+/// # let ezgl: ezgl::Ezgl = todo!();
+/// let program = Program::from_sources(&ezgl, "...", "...").unwrap();
+/// let mut watcher = ShaderWatcher::new(
+///     &program,
+///     &["shaders/quad.vert", "shaders/quad.frag"],
+///     |ezgl, sources| ezgl.program_from_sources(&sources[0], &sources[1]),
+/// )
+/// .unwrap();
+///
+/// // every frame
+/// if let Some(Err(err)) = watcher.poll(&ezgl) {
+///     eprintln!("{}", err);
+/// }
+/// program.bind(&ezgl);
+/// ```
+pub struct ShaderWatcher {
+    program: Program,
+    paths: Vec<PathBuf>,
+    relink: Relink,
+    events: Receiver<notify::Result<Event>>,
+    _watcher: RecommendedWatcher,
+}
+
+impl ShaderWatcher {
+    /// Start watching `paths`, rebuilding `program` with `relink` when any of them change.
+    ///
+    /// The parent directories are watched rather than the files themselves, so editors that save
+    /// by replacing the file are handled.
+    pub fn new<P, F>(program: &Program, paths: &[P], relink: F) -> notify::Result<Self>
+    where
+        P: AsRef<Path>,
+        F: FnMut(&EzglHandle, &[String]) -> Result<NativeProgram, ProgramError> + 'static,
+    {
+        let paths = paths
+            .iter()
+            .map(|path| path.as_ref().canonicalize())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let (sender, events) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(move |event| {
+            let _ = sender.send(event);
+        })?;
+
+        let mut dirs = paths
+            .iter()
+            .filter_map(|path| path.parent())
+            .collect::<Vec<_>>();
+        dirs.sort();
+        dirs.dedup();
+        for dir in dirs {
+            watcher.watch(dir, RecursiveMode::NonRecursive)?;
+        }
+
+        Ok(Self {
+            program: program.clone(),
+            paths,
+            relink: Box::new(relink),
+            events,
+            _watcher: watcher,
+        })
+    }
+
+    /// Rebuild the program if any watched file changed since the last poll.
+    ///
+    /// Returns None if nothing changed, otherwise whether the program was replaced.
+    pub fn poll(&mut self, ezgl: &EzglHandle) -> Option<Result<(), ReloadError>> {
+        let changed = self
+            .events
+            .try_iter()
+            .filter_map(|event| event.ok())
+            .filter(|event| event.kind.is_create() || event.kind.is_modify())
+            .any(|event| event.paths.iter().any(|path| self.paths.contains(path)));
+
+        if changed {
+            Some(self.reload(ezgl))
+        } else {
+            None
+        }
+    }
+
+    /// Rebuild the program now, whether or not any file changed.
+    pub fn reload(&mut self, ezgl: &EzglHandle) -> Result<(), ReloadError> {
+        let sources = self
+            .paths
+            .iter()
+            .map(|path| {
+                std::fs::read_to_string(path).map_err(|err| ReloadError::Io(path.clone(), err))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let program = (self.relink)(ezgl, &sources).map_err(ReloadError::Program)?;
+        self.program.replace(program);
+        Ok(())
+    }
+}
@@ -0,0 +1,73 @@
+//! Setup for the integration tests, which need a display and a GL driver. They're ignored by
+//! default, so run them with `cargo test --all-features -- --ignored`.
+
+use ezgl::{
+    gl::{self, HasContext, PixelPackData},
+    Ezgl, EzglBuilder, EzglHandle,
+};
+use winit::{
+    application::ApplicationHandler,
+    event::WindowEvent,
+    event_loop::{ActiveEventLoop, EventLoop},
+    window::{WindowAttributes, WindowId},
+};
+
+/// Run `f` with an [Ezgl] for a hidden window, set up with `builder` and error checking.
+///
+/// winit only allows one event loop per process, so each test binary can only call this once.
+pub fn with_ezgl<F: FnOnce(&Ezgl)>(builder: EzglBuilder, f: F) {
+    let mut event_loop = EventLoop::builder();
+
+    // the test harness doesn't run tests on the main thread
+    #[cfg(all(unix, not(target_os = "macos")))]
+    winit::platform::x11::EventLoopBuilderExtX11::with_any_thread(&mut event_loop, true);
+    #[cfg(windows)]
+    winit::platform::windows::EventLoopBuilderExtWindows::with_any_thread(&mut event_loop, true);
+
+    let event_loop = event_loop.build().expect("Could not create event loop");
+    event_loop
+        .run_app(&mut Test(Some((builder, f))))
+        .expect("Event loop failed");
+}
+
+struct Test<F>(Option<(EzglBuilder, F)>);
+
+impl<F: FnOnce(&Ezgl)> ApplicationHandler for Test<F> {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        let Some((builder, f)) = self.0.take() else {
+            return;
+        };
+
+        let window = event_loop
+            .create_window(WindowAttributes::default().with_visible(false))
+            .expect("Could not create window");
+        let ezgl = builder
+            .check_errors_in_debug(true)
+            .build_winit(&window)
+            .expect("Could not set up ezgl");
+
+        f(&ezgl);
+        event_loop.exit();
+    }
+
+    fn window_event(&mut self, _: &ActiveEventLoop, _: WindowId, _: WindowEvent) {}
+}
+
+/// Read the red channel of a `width` by `height` region of the current read framebuffer, with
+/// rows from bottom to top.
+#[allow(dead_code)] // not every test reads pixels
+pub fn read_red(ezgl: &EzglHandle, width: u32, height: u32) -> Vec<u8> {
+    let mut pixels = vec![0; (width * height * 4) as usize];
+    unsafe {
+        ezgl.read_pixels(
+            0,
+            0,
+            width as i32,
+            height as i32,
+            gl::RGBA,
+            gl::UNSIGNED_BYTE,
+            PixelPackData::Slice(&mut pixels),
+        )
+    };
+    pixels.chunks(4).map(|pixel| pixel[0]).collect()
+}
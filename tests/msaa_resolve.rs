@@ -0,0 +1,87 @@
+//! Renders a diagonal edge on a headless shared context, once into an [MsaaTarget] that is
+//! resolved into a [RenderTarget] and once into a [RenderTarget] directly, and checks that only
+//! the resolved edge has pixels between the two colors.
+
+mod common;
+
+use ezgl::{
+    framebuffer::{MsaaTarget, RenderTarget},
+    gl::{self, HasContext},
+    Ezgl, EzglHandle,
+};
+
+const SIZE: u32 = 64;
+const SAMPLES: u32 = 4;
+
+// white below the diagonal from the bottom left corner to the top right one, on black
+const VERTEX: &str = "
+const vec2 positions[3] = vec2[3](vec2(-1.0, -1.0), vec2(1.0, -1.0), vec2(1.0, 1.0));
+void main() { gl_Position = vec4(positions[gl_VertexID], 0.0, 1.0); }
+";
+const FRAGMENT: &str = "
+out vec4 color;
+void main() { color = vec4(1.0); }
+";
+
+fn draw_edge(ezgl: &EzglHandle, program: gl::NativeProgram) {
+    unsafe {
+        ezgl.viewport(0, 0, SIZE as i32, SIZE as i32);
+        ezgl.clear_color(0.0, 0.0, 0.0, 1.0);
+        ezgl.clear(gl::COLOR_BUFFER_BIT);
+        ezgl.use_program(Some(program));
+    }
+    ezgl.draw_fullscreen_triangle().unwrap();
+}
+
+#[test]
+#[ignore = "needs a display and a GL driver"]
+fn msaa_resolve_antialiases_edges() {
+    common::with_ezgl(Ezgl::builder(), |ezgl| {
+        let shared = ezgl.new_shared_headless(1, 1).unwrap();
+        std::thread::spawn(move || {
+            let ezgl = shared.make_current().unwrap();
+            let header = ezgl.glsl_version_header();
+            let program = ezgl
+                .program_from_sources(
+                    &format!("{}{}", header, VERTEX),
+                    &format!("{}{}", header, FRAGMENT),
+                )
+                .unwrap();
+
+            let aliased = RenderTarget::new(&ezgl, SIZE, SIZE, false).unwrap();
+            aliased.bind(&ezgl);
+            draw_edge(&ezgl, program);
+            let aliased = common::read_red(&ezgl, SIZE, SIZE);
+
+            let msaa = MsaaTarget::new(&ezgl, SIZE, SIZE, SAMPLES, 1, false).unwrap();
+            let resolved = RenderTarget::new(&ezgl, SIZE, SIZE, false).unwrap();
+            msaa.bind(&ezgl);
+            draw_edge(&ezgl, program);
+            msaa.resolve_to(&ezgl, &[resolved.framebuffer()]).unwrap();
+            resolved.bind(&ezgl);
+            let antialiased = common::read_red(&ezgl, SIZE, SIZE);
+
+            unsafe { ezgl.delete_program(program) };
+
+            // without MSAA every pixel is either covered or not
+            assert!(aliased.iter().all(|&red| red == 0 || red == 255));
+            assert!(aliased.contains(&0) && aliased.contains(&255));
+
+            // with it, the pixels the edge passes through are partly covered, and no others are
+            let edge = antialiased
+                .iter()
+                .enumerate()
+                .filter(|(_, &red)| red != 0 && red != 255)
+                .map(|(i, _)| ((i as u32 % SIZE) as i32, (i as u32 / SIZE) as i32))
+                .collect::<Vec<_>>();
+            assert!(
+                edge.len() >= SIZE as usize / 2,
+                "only {} partly covered pixels",
+                edge.len()
+            );
+            assert!(edge.iter().all(|(x, y)| (x - y).abs() <= 1), "{:?}", edge);
+        })
+        .join()
+        .unwrap();
+    });
+}
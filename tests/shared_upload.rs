@@ -0,0 +1,100 @@
+//! Uploads a texture on a worker thread through a shared headless context, and samples it on the
+//! main context.
+
+mod common;
+
+use ezgl::{
+    framebuffer::RenderTarget,
+    gl::{self, HasContext},
+    Ezgl,
+};
+
+const VERTEX: &str = "
+out vec2 uv;
+void main() {
+    uv = vec2((gl_VertexID << 1) & 2, gl_VertexID & 2);
+    gl_Position = vec4(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+";
+const FRAGMENT: &str = "
+uniform sampler2D tex;
+out vec4 color;
+void main() { color = texelFetch(tex, ivec2(gl_FragCoord.xy), 0); }
+";
+
+#[test]
+#[ignore = "needs a display and a GL driver"]
+fn texture_uploaded_on_worker_is_sampled_on_main() {
+    // the red channel of each texel of a 2x2 texture, from the bottom row up
+    const RED: [u8; 4] = [10, 80, 160, 240];
+
+    common::with_ezgl(Ezgl::builder(), |ezgl| {
+        let shared = ezgl.new_shared_headless(1, 1).unwrap();
+        let texture = std::thread::spawn(move || {
+            let shared = shared.make_current().unwrap();
+            let pixels = RED
+                .iter()
+                .flat_map(|&red| [red, 0, 0, 255])
+                .collect::<Vec<_>>();
+
+            unsafe {
+                let texture = shared.create_texture().unwrap();
+                shared.bind_texture(gl::TEXTURE_2D, Some(texture));
+                shared.tex_image_2d(
+                    gl::TEXTURE_2D,
+                    0,
+                    gl::RGBA8 as i32,
+                    2,
+                    2,
+                    0,
+                    gl::RGBA,
+                    gl::UNSIGNED_BYTE,
+                    Some(&pixels),
+                );
+                shared.tex_parameter_i32(
+                    gl::TEXTURE_2D,
+                    gl::TEXTURE_MIN_FILTER,
+                    gl::NEAREST as i32,
+                );
+                shared.tex_parameter_i32(
+                    gl::TEXTURE_2D,
+                    gl::TEXTURE_MAG_FILTER,
+                    gl::NEAREST as i32,
+                );
+                shared.bind_texture(gl::TEXTURE_2D, None);
+
+                // the upload has to be finished before the main thread uses the texture
+                shared.finish();
+                texture
+            }
+        })
+        .join()
+        .unwrap();
+
+        let header = ezgl.glsl_version_header();
+        let program = ezgl
+            .program_from_sources(
+                &format!("{}{}", header, VERTEX),
+                &format!("{}{}", header, FRAGMENT),
+            )
+            .unwrap();
+        let target = RenderTarget::new(ezgl, 2, 2, false).unwrap();
+
+        target.bind(ezgl);
+        unsafe {
+            ezgl.viewport(0, 0, 2, 2);
+            ezgl.use_program(Some(program));
+            ezgl.active_texture(gl::TEXTURE0);
+            ezgl.bind_texture(gl::TEXTURE_2D, Some(texture));
+        }
+        ezgl.draw_fullscreen_triangle().unwrap();
+        let red = common::read_red(ezgl, 2, 2);
+
+        unsafe {
+            ezgl.delete_program(program);
+            ezgl.delete_texture(texture);
+        }
+
+        assert_eq!(red, RED);
+    });
+}
@@ -0,0 +1,38 @@
+//! Doubles an array of floats in a [StorageBuffer] with a compute shader.
+
+mod common;
+
+use ezgl::{compute::MemoryBarrier, gl::HasContext, shader::Program, storage::StorageBuffer, Ezgl};
+
+const COMPUTE: &str = "
+layout(local_size_x = 64) in;
+layout(std430, binding = 0) buffer Data { float values[]; };
+void main() { values[gl_GlobalInvocationID.x] *= 2.0; }
+";
+
+#[test]
+#[ignore = "needs a display and a GL driver"]
+fn compute_shader_doubles_storage_buffer() {
+    common::with_ezgl(Ezgl::builder().gl_version(4, 3), |ezgl| {
+        let header = if ezgl.version().is_embedded {
+            "#version 310 es\n"
+        } else {
+            "#version 430\n"
+        };
+        let program =
+            Program::from_compute_source(ezgl, &format!("{}{}", header, COMPUTE)).unwrap();
+
+        let input = (0..256).map(|i| i as f32 - 100.5).collect::<Vec<_>>();
+        let buffer = StorageBuffer::new(ezgl, &input).unwrap();
+        buffer.bind(ezgl, 0);
+
+        program.bind(ezgl);
+        ezgl.dispatch_compute(input.len() as u32 / 64, 1, 1)
+            .unwrap();
+        ezgl.memory_barrier(MemoryBarrier::BUFFER_UPDATE).unwrap();
+
+        let output = buffer.read_back(ezgl).unwrap();
+        let doubled = input.iter().map(|value| value * 2.0).collect::<Vec<_>>();
+        assert_eq!(output, doubled);
+    });
+}